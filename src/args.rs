@@ -32,10 +32,97 @@ pub enum Commands {
         /// Run as a background daemon
         #[arg(long)]
         daemon: bool,
+
+        /// run using a custom path config file
+        #[arg(long)]
+        path: Option<PathBuf>,
     },
 
     /// Show what neatd WOULD do without moving files
-    DryRun,
+    DryRun {
+        /// Print every scanned entry and the rule it matched instead of the plan
+        #[arg(long)]
+        scan: bool,
+    },
+
+    /// Build the plan and print it, or save it for a later `apply`
+    Plan {
+        /// Write the plan as JSON to this file instead of printing it
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// plan using a custom path config file
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Execute exactly the operations of a saved plan file
+    Apply {
+        /// Plan file written by `neatd plan --out`
+        plan_file: PathBuf,
+
+        /// apply using a custom path config file
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Move the files of an earlier run back where they came from
+    Undo {
+        /// Run to undo (default: the most recent run)
+        run_id: Option<String>,
+
+        /// Only undo operations made by this rule
+        #[arg(long)]
+        rule: Option<String>,
+
+        /// Only undo files whose original path (relative to its root) matches this glob
+        #[arg(long)]
+        glob: Option<String>,
+
+        /// undo using a custom path config file
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Finish the remaining operations of an interrupted run
+    Resume {
+        /// Interrupted run to resume (default: the most recent one)
+        run_id: Option<String>,
+
+        /// resume using a custom path config file
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Undo the operations an interrupted run already did
+    Rollback {
+        /// Interrupted run to roll back (default: the most recent one)
+        run_id: Option<String>,
+
+        /// roll back using a custom path config file
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// List past runs, or show the operations of one run
+    History {
+        #[command(subcommand)]
+        command: Option<HistoryCommands>,
+
+        /// history using a custom path config file
+        #[arg(long, global = true)]
+        path: Option<PathBuf>,
+    },
+
+    /// List, restore or purge quarantined files
+    Quarantine {
+        #[command(subcommand)]
+        command: Option<QuarantineCommands>,
+
+        /// quarantine using a custom path config file
+        #[arg(long, global = true)]
+        path: Option<PathBuf>,
+    },
 
     /// Show whether daemon is running and summary stats
     Status,
@@ -51,6 +138,16 @@ pub enum Commands {
         path: Option<PathBuf>,
     },
 
+    /// Explain why a file would be ignored, matched or skipped
+    Explain {
+        /// File to explain (relative paths are resolved from the current directory)
+        file: PathBuf,
+
+        /// explain using a custom path config file
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
     /// Display your config in a nice colored fashion
     PrintConfig {
         /// display custom path config file contents
@@ -58,3 +155,50 @@ pub enum Commands {
         path: Option<PathBuf>,
     },
 }
+
+#[derive(Subcommand, Debug)]
+pub enum HistoryCommands {
+    /// List every recorded run (the default)
+    List,
+
+    /// Print the full operation list of one run
+    Show {
+        /// Run to show
+        run_id: String,
+
+        /// Only operations made by this rule
+        #[arg(long)]
+        rule: Option<String>,
+
+        /// Only operations with this action
+        #[arg(long, value_parser = ["move", "copy", "delete", "skip"])]
+        action: Option<String>,
+
+        /// Only operations with this status
+        #[arg(long, value_parser = ["committed", "failed", "pending", "undone", "not_run", "skipped"])]
+        status: Option<String>,
+
+        /// Only sources whose path (relative to its root) matches this glob
+        #[arg(long)]
+        glob: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum QuarantineCommands {
+    /// List quarantined files with their original path (the default)
+    List,
+
+    /// Move quarantined files back to their original path
+    Restore {
+        /// Entry id, or a glob matched against the original path
+        target: String,
+    },
+
+    /// Delete quarantined files for good (needs safety.allow_delete = true)
+    Purge {
+        /// Only files quarantined longer than this, e.g. "30d"
+        #[arg(long)]
+        older_than: String,
+    },
+}
@@ -18,6 +18,7 @@ created_at = {:?}
 mode = "dry_run"
 default_action = "move"
 recursive = true
+# settle = "60s"
 
 [paths]
 roots = ["/Users/you/Downloads"]
@@ -53,6 +54,8 @@ format = "text"
 [safety]
 require_within_roots = true
 allow_delete = false
+# quarantine_retention = "30d"
+# protected = ["**/*.kdbx"]
 
 
 [[rules]]
@@ -67,6 +70,7 @@ extensions = ["png", "jpg", "jpeg", "gif", "webp", "heic", "tiff"]
 type = "move"
 to = "images"
 use_layout = true
+conflict_strategy = "rename"
 
 [[rules]]
 name = "Videos"
@@ -80,6 +84,7 @@ extensions = ["mp4", "mov", "mkv", "avi", "webm"]
 type = "move"
 to = "videos"
 use_layout = true
+conflict_strategy = "rename"
 
 [[rules]]
 name = "Documents"
@@ -93,6 +98,7 @@ extensions = ["pdf", "doc", "docx", "ppt", "pptx", "xls", "xlsx", "txt", "md"]
 type = "move"
 to = "documents"
 use_layout = true
+conflict_strategy = "rename"
 
 [[rules]]
 name = "Archives"
@@ -106,6 +112,7 @@ extensions = ["zip", "rar", "7z", "tar", "gz"]
 type = "move"
 to = "archives"
 use_layout = false
+conflict_strategy = "rename"
 
 [[rules]]
 name = "Code"
@@ -119,6 +126,7 @@ extensions = ["rs", "py", "js", "ts", "java", "go", "cpp", "c", "h", "cs"]
 type = "move"
 to = "code"
 use_layout = false
+conflict_strategy = "rename"
 
 [[rules]]
 name = "Fallback"
@@ -132,6 +140,7 @@ any = true
 type = "move"
 to = "other"
 use_layout = false
+conflict_strategy = "rename"
 
             
             "#,
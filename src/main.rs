@@ -5,15 +5,48 @@ mod init;
 mod parse;
 mod run;
 
-use crate::run::scanner::walk_policy_setup;
-use args::{Cli, Commands};
+use crate::run::{
+    executor::{
+        execute_journaled,
+        verify::{verify_deletes, verify_sources},
+    },
+    journal::record::RunMode,
+    planner::{
+        build_plan,
+        display::display_plan,
+        plan_file::{load_plan, save_plan},
+        protected::enforce_protected,
+        roots::enforce_roots,
+        settle::record_unsettled,
+    },
+    scanner::{display::display_scan, walk_policy_setup},
+};
+use args::{Cli, Commands, HistoryCommands, QuarantineCommands};
 use clap::Parser;
+use colored::Colorize;
 use config_file_data::config_file_data;
 use directory::{get_file_path, get_hom_directory};
 use init::create_or_override_config_file;
 use parse::read_config;
-use run::config::display::display_config;
-use std::path::PathBuf;
+use run::{
+    config::{
+        config::{ExecutionMode, ReportType, resolve_path},
+        display::display_config,
+        units::parse_duration,
+    },
+    entries::fs_entry::EntryError,
+    explain::explain_path,
+    history::{
+        HistoryFilter,
+        display::{display_run, display_runs, print_json},
+        run_operations, summarize_runs,
+    },
+    quarantine::{display::display_entries, purge, read_index, restore},
+    recover::{report_incomplete, resume_run, rollback_run},
+    scanner::display::display_error,
+    undo::{UndoFilter, undo_run},
+};
+use std::{env::current_dir, path::PathBuf, process::exit};
 
 fn main() {
     let cli: Cli = Cli::parse();
@@ -23,11 +56,21 @@ fn main() {
             println!("Noiceee you have initialized.. now you can edit the config file!!!");
             _ = create_or_override_config_file("config.toml", config_file_data(), force, path);
         }
-        Some(Commands::DryRun) => {
+        Some(Commands::DryRun { scan: show_scan }) => {
             let config_file_path: PathBuf = get_file_path(get_hom_directory(), "config.toml");
             let config = read_config(&config_file_path);
             match config {
-                Ok(config_value) => walk_policy_setup(&config_value, &config_file_path),
+                Ok(config_value) => {
+                    // relative roots are resolved from the current working directory
+                    let base_dir: PathBuf = current_dir().unwrap_or_default();
+                    let scan = walk_policy_setup(&config_value, &base_dir);
+                    if show_scan {
+                        display_scan(&scan);
+                        return;
+                    }
+                    let plan = build_plan(&config_value, &base_dir, scan);
+                    display_plan(&plan);
+                }
                 Err(error) => {
                     eprintln!(
                         "Failed to read config at {:?}: {:?}",
@@ -36,12 +79,276 @@ fn main() {
                 }
             }
         }
+        Some(Commands::Plan { out, path }) => {
+            let config_file_path: PathBuf =
+                path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+            let Ok(config_value) = read_config(&config_file_path) else {
+                exit(1);
+            };
+            let base_dir: PathBuf = current_dir().unwrap_or_default();
+            let scan = walk_policy_setup(&config_value, &base_dir);
+            let plan = build_plan(&config_value, &base_dir, scan);
+            match out {
+                Some(out) => {
+                    let operations = plan.operations.len();
+                    if let Err(error) = save_plan(&plan.operations, &out) {
+                        eprintln!("{}", error);
+                        exit(1);
+                    }
+                    println!("Wrote {} operations to {:?}", operations, out);
+                }
+                None => display_plan(&plan),
+            }
+        }
+        Some(Commands::Apply { plan_file, path }) => {
+            let config_file_path: PathBuf =
+                path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+            let Ok(config_value) = read_config(&config_file_path) else {
+                exit(1);
+            };
+            let mut plan = match load_plan(&plan_file) {
+                Ok(plan) => plan,
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            };
+            let base_dir: PathBuf = current_dir().unwrap_or_default();
+            enforce_roots(&config_value, &base_dir, &mut plan.operations);
+            enforce_protected(&config_value, &mut plan.operations);
+            let refused = verify_deletes(&plan.operations, config_value.safety.allow_delete);
+            if !refused.is_empty() {
+                eprintln!(
+                    "{}",
+                    "The plan deletes files, nothing was applied:".red().bold()
+                );
+                for line in refused {
+                    eprintln!("  {} {}", "•".red().bold(), line);
+                }
+                exit(1);
+            }
+            let stale = verify_sources(&plan.operations);
+            if !stale.is_empty() {
+                eprintln!(
+                    "{}",
+                    "The plan no longer matches the filesystem, nothing was applied:"
+                        .red()
+                        .bold()
+                );
+                for line in stale {
+                    eprintln!("  {} {}", "•".red().bold(), line);
+                }
+                exit(1);
+            }
+            let state_dir = resolve_path(
+                &current_dir().unwrap_or_default(),
+                &config_value.paths.state_dir,
+            );
+            if report_incomplete(&state_dir) {
+                exit(1);
+            }
+            match execute_journaled(&state_dir, RunMode::Apply, &plan.operations) {
+                Ok(errors) if !errors.iter().any(EntryError::is_failure) => {}
+                Ok(_) => exit(1),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
+        }
+        Some(Commands::Undo {
+            run_id,
+            rule,
+            glob,
+            path,
+        }) => {
+            let config_file_path: PathBuf =
+                path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+            let Ok(config_value) = read_config(&config_file_path) else {
+                exit(1);
+            };
+            let state_dir = resolve_path(
+                &current_dir().unwrap_or_default(),
+                &config_value.paths.state_dir,
+            );
+            if report_incomplete(&state_dir) {
+                exit(1);
+            }
+            match undo_run(&state_dir, run_id.as_deref(), &UndoFilter { rule, glob }) {
+                Ok(true) => {}
+                Ok(false) => exit(1),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
+        }
+        Some(Commands::Resume { run_id, path }) => {
+            let config_file_path: PathBuf =
+                path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+            let Ok(config_value) = read_config(&config_file_path) else {
+                exit(1);
+            };
+            let state_dir = resolve_path(
+                &current_dir().unwrap_or_default(),
+                &config_value.paths.state_dir,
+            );
+            match resume_run(&config_value, &state_dir, run_id.as_deref()) {
+                Ok(true) => {}
+                Ok(false) => exit(1),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
+        }
+        Some(Commands::Rollback { run_id, path }) => {
+            let config_file_path: PathBuf =
+                path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+            let Ok(config_value) = read_config(&config_file_path) else {
+                exit(1);
+            };
+            let state_dir = resolve_path(
+                &current_dir().unwrap_or_default(),
+                &config_value.paths.state_dir,
+            );
+            match rollback_run(&state_dir, run_id.as_deref()) {
+                Ok(true) => {}
+                Ok(false) => exit(1),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
+        }
+        Some(Commands::History { command, path }) => {
+            let config_file_path: PathBuf =
+                path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+            let Ok(config_value) = read_config(&config_file_path) else {
+                exit(1);
+            };
+            let state_dir = resolve_path(
+                &current_dir().unwrap_or_default(),
+                &config_value.paths.state_dir,
+            );
+            // json for report.format = "json", every other format reads as text
+            let json = matches!(config_value.report.format, ReportType::Json);
+            let result = match command.unwrap_or(HistoryCommands::List) {
+                HistoryCommands::List => summarize_runs(&state_dir).and_then(|runs| {
+                    if json {
+                        print_json(&runs)
+                    } else {
+                        display_runs(&runs);
+                        Ok(())
+                    }
+                }),
+                HistoryCommands::Show {
+                    run_id,
+                    rule,
+                    action,
+                    status,
+                    glob,
+                } => {
+                    let filter = HistoryFilter {
+                        rule,
+                        action,
+                        status,
+                        glob,
+                    };
+                    run_operations(&state_dir, &run_id, &filter).and_then(|detail| {
+                        if json {
+                            print_json(&detail)
+                        } else {
+                            display_run(&detail);
+                            Ok(())
+                        }
+                    })
+                }
+            };
+            if let Err(error) = result {
+                eprintln!("{}", error);
+                exit(1);
+            }
+        }
+        Some(Commands::Quarantine { command, path }) => {
+            let config_file_path: PathBuf =
+                path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+            let Ok(config_value) = read_config(&config_file_path) else {
+                exit(1);
+            };
+            let quarantine = resolve_path(
+                &current_dir().unwrap_or_default(),
+                &config_value.paths.quarantine,
+            );
+            let result = match command.unwrap_or(QuarantineCommands::List) {
+                QuarantineCommands::List => read_index(&quarantine).and_then(|entries| {
+                    if matches!(config_value.report.format, ReportType::Json) {
+                        print_json(&entries).map(|()| true)
+                    } else {
+                        display_entries(&entries);
+                        Ok(true)
+                    }
+                }),
+                QuarantineCommands::Restore { target } => restore(&quarantine, &target),
+                QuarantineCommands::Purge { .. } if !config_value.safety.allow_delete => Err(
+                    "purging deletes files for good, it needs safety.allow_delete = true"
+                        .to_string(),
+                ),
+                QuarantineCommands::Purge { older_than } => parse_duration(&older_than)
+                    .and_then(|older_than| purge(&quarantine, older_than)),
+            };
+            match result {
+                Ok(true) => {}
+                Ok(false) => exit(1),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    exit(1);
+                }
+            }
+        }
         Some(Commands::Status) => {
             println!("Okay so you want to see if daemon is running in background or not!!!");
         }
-        Some(Commands::Run { once, .. }) => {
+        Some(Commands::Run { once, path, .. }) => {
             if once {
-                println!("okay soo you decided to run the neatd just once.. cool cool cool!!!");
+                let config_file_path: PathBuf =
+                    path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+                let Ok(config_value) = read_config(&config_file_path) else {
+                    exit(1);
+                };
+                let base_dir: PathBuf = current_dir().unwrap_or_default();
+                let scan = walk_policy_setup(&config_value, &base_dir);
+                let plan = build_plan(&config_value, &base_dir, scan);
+                match config_value.general.mode {
+                    // dry_run never touches the filesystem, it only shows the plan
+                    ExecutionMode::DryRun => display_plan(&plan),
+                    ExecutionMode::Run => {
+                        let state_dir = resolve_path(&base_dir, &config_value.paths.state_dir);
+                        if report_incomplete(&state_dir) {
+                            exit(1);
+                        }
+                        if let Some(error) = record_unsettled(&state_dir, &plan.operations) {
+                            display_error(&error);
+                        }
+                        let result = execute_journaled(&state_dir, RunMode::Run, &plan.operations);
+                        if let Some(retention) = config_value.safety.retention {
+                            let quarantine =
+                                resolve_path(&base_dir, &config_value.paths.quarantine);
+                            println!("\n{}", "Quarantine retention".bright_yellow().bold());
+                            if let Err(error) = purge(&quarantine, retention) {
+                                eprintln!("{}", error);
+                            }
+                        }
+                        match result {
+                            Ok(errors) if !errors.iter().any(EntryError::is_failure) => {}
+                            Ok(_) => exit(1),
+                            Err(error) => {
+                                eprintln!("{}", error);
+                                exit(1);
+                            }
+                        }
+                    }
+                }
             } else {
                 println!(
                     "okay soo you decided to run the neatd as a background daemon.. cool cool cool!!!"
@@ -51,7 +358,20 @@ fn main() {
         Some(Commands::Validate { path }) => {
             let config_file_path: PathBuf =
                 path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
-            _ = read_config(&config_file_path);
+            if read_config(&config_file_path).is_err() {
+                exit(1);
+            }
+        }
+        Some(Commands::Explain { file, path }) => {
+            let config_file_path: PathBuf =
+                path.unwrap_or(get_file_path(get_hom_directory(), "config.toml"));
+            match read_config(&config_file_path) {
+                Ok(config_value) => {
+                    let base_dir: PathBuf = current_dir().unwrap_or_default();
+                    explain_path(&config_value, &base_dir, &file);
+                }
+                Err(_) => exit(1),
+            }
         }
         Some(Commands::PrintConfig { path }) => {
             let config_file_path: PathBuf =
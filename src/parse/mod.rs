@@ -1,4 +1,4 @@
-use crate::run::config::config::Config;
+use crate::run::config::{config::Config, validate::validate_config};
 use colored::Colorize;
 use std::{fs::read_to_string, path::PathBuf};
 use toml::from_str;
@@ -6,14 +6,20 @@ use toml::from_str;
 pub fn read_config(path: &PathBuf) -> Result<Config, ()> {
     let content = read_to_string(path).map_err(|e| {
         eprintln!("Failed to read config at {:?}: {}", path, e);
-        ()
     })?;
 
-    let config: Config = from_str(&content).map_err(|e| {
+    let mut config: Config = from_str(&content).map_err(|e| {
         eprintln!("Failed to parse TOML in {:?}: {}", path, e);
-        ()
     })?;
 
-    println!("{}", "Config file validated!".green().bold());
+    validate_config(&mut config).map_err(|errors| {
+        eprintln!("Invalid config at {:?}:", path);
+        for error in errors {
+            eprintln!("  {} {}", "•".red().bold(), error);
+        }
+    })?;
+
+    // stderr, so machine readable output on stdout stays clean
+    eprintln!("{}", "Config file validated!".green().bold());
     Ok(config)
 }
@@ -1,5 +1,12 @@
+use crate::run::{config::template::Template, policies::config_policy::ConfigPolicy};
+use globset::GlobSet;
+use regex::Regex;
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 #[derive(Deserialize, Debug)]
 pub struct Config {
@@ -15,6 +22,29 @@ pub struct Config {
     pub report: Report,
     pub safety: Safety,
     pub rules: Vec<Rule>,
+    // indexes into `rules`: enabled rules only, ascending priority, ties keep file order
+    #[serde(skip)]
+    pub rule_order: Vec<usize>,
+}
+
+impl Config {
+    // the rule's own settle window, else the general one
+    pub fn settle_for(&self, rule: Option<&str>) -> Option<Duration> {
+        self.rules
+            .iter()
+            .find(|candidate| Some(candidate.name.as_str()) == rule)
+            .and_then(|candidate| candidate.settle_window)
+            .or(self.general.settle_window)
+    }
+
+    pub fn rules_by_priority(&self) -> impl Iterator<Item = &Rule> {
+        self.rule_order.iter().map(|index| &self.rules[*index])
+    }
+}
+
+// relative paths in [paths] are taken from the directory neatd runs in
+pub fn resolve_path(base_dir: &Path, path: &Path) -> PathBuf {
+    ConfigPolicy::lexical_normalize(&base_dir.join(path))
 }
 
 #[derive(Deserialize, Debug)]
@@ -22,20 +52,29 @@ pub struct General {
     pub mode: ExecutionMode,
     pub default_action: ActionType,
     pub recursive: bool,
+    // files must be unchanged this long before they are acted on, e.g. "60s"
+    #[serde(default)]
+    pub settle: Option<String>,
+    // settle parsed by validate_config
+    #[serde(skip)]
+    pub settle_window: Option<Duration>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ExecutionMode {
-    dry_run,
-    run,
+    DryRun,
+    Run,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ActionType {
     Copy,
     Move,
     Delete,
+    // a move that gives the file a new name, in its own directory unless `to` is set
+    Rename,
 }
 
 #[derive(Deserialize, Debug)]
@@ -47,22 +86,109 @@ pub struct ConfigPaths {
 
 #[derive(Deserialize, Debug)]
 pub struct Ignore {
-    pub globs: Vec<PathBuf>,
+    pub globs: Vec<String>,
     pub ignore_hidden: bool,
-    pub extensions: Vec<PathBuf>,
+    pub extensions: Vec<String>,
+    // filled in by validate_config, never read from the file
+    #[serde(skip)]
+    pub compiled: CompiledIgnore,
+}
+
+#[derive(Debug, Default)]
+pub struct CompiledIgnore {
+    pub globs: GlobSet,
+    // `<dir>/**` globs reduced to `<dir>` so a whole subtree can be pruned at once
+    pub dir_globs: GlobSet,
+    // index into `Ignore::globs` for every glob in `dir_globs`
+    pub dir_glob_sources: Vec<usize>,
+    pub extensions: HashSet<String>,
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Naming {
     pub normalize_names: bool,
+    // the steps below only run with normalize_names
+    // replace non-ASCII letters with their closest ASCII spelling ("é" -> "e")
+    #[serde(default)]
+    pub transliterate: bool,
+    #[serde(default)]
+    pub case: NameCase,
+    // keep browser duplicate suffixes like "report (1).pdf"
+    #[serde(default)]
+    pub keep_copy_suffix: bool,
+    // in bytes, extension included
+    #[serde(default)]
+    pub max_length: Option<usize>,
+}
+
+impl Naming {
+    // common limit of a file name on Linux, macOS and Windows
+    pub const MAX_LENGTH: usize = 255;
+
+    pub fn max_length(&self) -> usize {
+        self.max_length.unwrap_or(Self::MAX_LENGTH)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NameCase {
+    #[default]
+    Preserve,
+    Lower,
+    // lowercase, runs of anything but letters and digits become "-"
+    Slug,
+}
+
+impl NameCase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NameCase::Preserve => "preserve",
+            NameCase::Lower => "lower",
+            NameCase::Slug => "slug",
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
 pub struct Layout {
-    pub date_source: String,
+    pub date_source: DateSource,
     pub date_format: String,
 }
 
+// Timestamp used for `use_layout` directories. A missing timestamp falls back
+// to the next one in `DateSource::fallback_chain`, `now` always exists.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DateSource {
+    Modified,
+    Created,
+    Accessed,
+    Now,
+}
+
+impl DateSource {
+    // the configured source first, then the others in declaration order
+    pub fn fallback_chain(self) -> impl Iterator<Item = DateSource> {
+        let order = [
+            DateSource::Modified,
+            DateSource::Created,
+            DateSource::Accessed,
+            DateSource::Now,
+        ];
+        std::iter::once(self).chain(order.into_iter().filter(move |source| *source != self))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DateSource::Modified => "modified",
+            DateSource::Created => "created",
+            DateSource::Accessed => "accessed",
+            DateSource::Now => "now",
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Log {
     pub level: LogType,
@@ -85,6 +211,7 @@ pub struct Report {
 #[serde(rename_all = "lowercase")]
 pub enum ReportType {
     Text,
+    Json,
     SpreadSheet,
     Analytics,
 }
@@ -93,6 +220,18 @@ pub enum ReportType {
 pub struct Safety {
     pub require_within_roots: bool,
     pub allow_delete: bool,
+    // quarantined files older than this are purged after every run, e.g. "30d"
+    #[serde(default)]
+    pub quarantine_retention: Option<String>,
+    // quarantine_retention parsed by validate_config
+    #[serde(skip)]
+    pub retention: Option<Duration>,
+    // never a source or destination, matched against the absolute path and
+    // the path relative to the root
+    #[serde(default)]
+    pub protected: Vec<String>,
+    #[serde(skip)]
+    pub protected_globs: GlobSet,
 }
 
 #[derive(Deserialize, Debug)]
@@ -100,6 +239,11 @@ pub struct Rule {
     pub name: String,
     pub enabled: bool,
     pub priority: i64,
+    // overrides general.settle for the files this rule matches
+    #[serde(default)]
+    pub settle: Option<String>,
+    #[serde(skip)]
+    pub settle_window: Option<Duration>,
     pub r#match: Option<Match>,
     pub action: Option<Action>,
 }
@@ -108,6 +252,25 @@ pub struct Rule {
 pub struct Match {
     pub extensions: Option<Vec<String>>,
     pub any: Option<bool>,
+    // globs against the file name only, e.g. "Screenshot*.png"
+    pub name_globs: Option<Vec<String>>,
+    // globs against the path relative to the root, e.g. "projects/**/*.pdf"
+    pub path_globs: Option<Vec<String>>,
+    pub name_regex: Option<String>,
+    // path prefix relative to the root the file was found under
+    pub under: Option<PathBuf>,
+    // sizes with units, e.g. "500MB" or "2GiB"
+    pub min_size: Option<String>,
+    pub max_size: Option<String>,
+    // ages of the last modification, e.g. "30d" or "12h"
+    pub older_than: Option<String>,
+    pub newer_than: Option<String>,
+    // nested match trees, combined with the criteria above using AND
+    pub all: Option<Vec<Match>>,
+    pub any_of: Option<Vec<Match>>,
+    pub not: Option<Box<Match>>,
+    #[serde(skip)]
+    pub compiled: CompiledMatch,
 }
 
 impl Match {
@@ -115,6 +278,18 @@ impl Match {
         Self {
             extensions: Some(Vec::new()),
             any: Some(false),
+            name_globs: None,
+            path_globs: None,
+            name_regex: None,
+            under: None,
+            min_size: None,
+            max_size: None,
+            older_than: None,
+            newer_than: None,
+            all: None,
+            any_of: None,
+            not: None,
+            compiled: CompiledMatch::default(),
         }
     }
 }
@@ -125,9 +300,64 @@ impl Default for Match {
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Debug, Default)]
+pub struct CompiledMatch {
+    pub extensions: HashSet<String>,
+    // `under` without "." components, "./archive" is "archive"
+    pub under: Option<PathBuf>,
+    pub name_globs: Option<GlobSet>,
+    pub path_globs: Option<GlobSet>,
+    pub name_regex: Option<Regex>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub older_than: Option<Duration>,
+    pub newer_than: Option<Duration>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
 pub struct Action {
     pub r#type: ActionType,
+    // required for copy and move, optional for rename
+    #[serde(default)]
     pub to: PathBuf,
     pub use_layout: bool,
+    #[serde(default)]
+    pub conflict_strategy: ConflictStrategy,
+    // new file name, a template like `to`
+    #[serde(default)]
+    pub name: Option<String>,
+    // regex replaced in the file name before `name` is rendered
+    #[serde(default)]
+    pub pattern: Option<String>,
+    // may refer to capture groups of `pattern` as $1 or ${name}
+    #[serde(default)]
+    pub replacement: String,
+    // `to`, `name` and `pattern` compiled by validate_config
+    #[serde(skip)]
+    pub template: Template,
+    #[serde(skip)]
+    pub name_template: Option<Template>,
+    #[serde(skip)]
+    pub name_regex: Option<Regex>,
+}
+
+impl Action {
+    // whether the file ends up with a name other than its own
+    pub fn renames(&self) -> bool {
+        self.name.is_some() || self.pattern.is_some()
+    }
+}
+
+// What the planner does when the destination is already taken, either on disk
+// or by an earlier operation in the same plan.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    #[default]
+    Rename,
+    KeepNewest,
+    KeepOldest,
+    Quarantine,
+    Skip,
+    Overwrite,
 }
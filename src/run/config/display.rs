@@ -1,4 +1,6 @@
-use super::config::{ActionType, Config, ExecutionMode, LogType, ReportType};
+use super::config::{
+    ActionType, Config, ConflictStrategy, ExecutionMode, LogType, Match, ReportType,
+};
 use colored::*;
 
 fn section(title: &str) {
@@ -28,8 +30,8 @@ fn opt_str(v: Option<&str>) -> ColoredString {
 
 fn mode_val(m: &ExecutionMode) -> ColoredString {
     match m {
-        ExecutionMode::dry_run => "dry_run".yellow().bold(),
-        ExecutionMode::run => "run".green().bold(),
+        ExecutionMode::DryRun => "dry_run".yellow().bold(),
+        ExecutionMode::Run => "run".green().bold(),
     }
 }
 
@@ -38,6 +40,18 @@ fn action_val(a: &ActionType) -> ColoredString {
         ActionType::Copy => "copy".cyan().bold(),
         ActionType::Move => "move".bright_blue().bold(),
         ActionType::Delete => "delete".red().bold(),
+        ActionType::Rename => "rename".magenta().bold(),
+    }
+}
+
+fn conflict_val(c: &ConflictStrategy) -> ColoredString {
+    match c {
+        ConflictStrategy::Rename => "rename".green().bold(),
+        ConflictStrategy::KeepNewest => "keep_newest".cyan().bold(),
+        ConflictStrategy::KeepOldest => "keep_oldest".cyan().bold(),
+        ConflictStrategy::Quarantine => "quarantine".yellow().bold(),
+        ConflictStrategy::Skip => "skip".bright_black().bold(),
+        ConflictStrategy::Overwrite => "overwrite".red().bold(),
     }
 }
 
@@ -52,11 +66,80 @@ fn log_val(l: &LogType) -> ColoredString {
 fn report_val(r: &ReportType) -> ColoredString {
     match r {
         ReportType::Text => "text".white().bold(),
+        ReportType::Json => "json".yellow().bold(),
         ReportType::SpreadSheet => "spreadsheet".magenta().bold(),
         ReportType::Analytics => "analytics".bright_cyan().bold(),
     }
 }
 
+fn list_val(indent: &str, k: &str, values: Option<&Vec<String>>) {
+    match values {
+        Some(values) if !values.is_empty() => {
+            println!("{}{}", indent, key(k));
+            for v in values {
+                println!("{}  {} {}", indent, "•".bright_black(), v.magenta());
+            }
+        }
+        _ => println!("{}{} {}", indent, key(k), "<none>".bright_black().italic()),
+    }
+}
+
+// nested matches only list the fields that are actually set
+fn display_match(m: &Match, indent: usize, nested: bool) {
+    let pad = " ".repeat(indent);
+    if !nested || m.any.is_some() {
+        let any = m.any.unwrap_or(false);
+        println!("{}{} {}", pad, key("any"), bool_val(any));
+    }
+    if !nested || m.extensions.is_some() {
+        list_val(&pad, "extensions", m.extensions.as_ref());
+    }
+
+    // the pattern matchers are optional, only show the ones that are set
+    if m.name_globs.is_some() {
+        list_val(&pad, "name_globs", m.name_globs.as_ref());
+    }
+    if m.path_globs.is_some() {
+        list_val(&pad, "path_globs", m.path_globs.as_ref());
+    }
+    if let Some(regex) = m.name_regex.as_ref() {
+        println!("{}{} {}", pad, key("name_regex"), regex.magenta());
+    }
+    let constraints = [
+        ("min_size", &m.min_size),
+        ("max_size", &m.max_size),
+        ("older_than", &m.older_than),
+        ("newer_than", &m.newer_than),
+    ];
+    for (k, v) in constraints {
+        if let Some(v) = v {
+            println!("{}{} {}", pad, key(k), v.cyan());
+        }
+    }
+    if let Some(under) = m.under.as_ref() {
+        println!(
+            "{}{} {}",
+            pad,
+            key("under"),
+            under.to_string_lossy().green()
+        );
+    }
+
+    for (k, children) in [("all", &m.all), ("any_of", &m.any_of)] {
+        if let Some(children) = children {
+            println!("{}{}", pad, k.bright_blue().bold());
+            for (i, child) in children.iter().enumerate() {
+                println!("{}  {}", pad, format!("[{}]", i).bright_black());
+                display_match(child, indent + 4, true);
+            }
+        }
+    }
+    if let Some(not) = m.not.as_ref() {
+        println!("{}{}", pad, "not".red().bold());
+        display_match(not, indent + 4, true);
+    }
+}
+
 pub fn display_config(config: &Config) {
     println!("\n{}", "YOUR CONFIG".bright_yellow().bold());
     println!("{}", "═══════════".bright_black());
@@ -82,6 +165,11 @@ pub fn display_config(config: &Config) {
         key("recursive"),
         bool_val(config.general.recursive)
     );
+    println!(
+        "{} {}",
+        key("settle"),
+        opt_str(config.general.settle.as_deref())
+    );
 
     section("PATHS");
     println!(
@@ -115,11 +203,7 @@ pub fn display_config(config: &Config) {
         println!("  {}", "<none>".bright_black().italic());
     } else {
         for ext in &config.ignore.extensions {
-            println!(
-                "  {} {}",
-                "•".bright_black(),
-                ext.to_string_lossy().magenta()
-            );
+            println!("  {} {}", "•".bright_black(), ext.magenta());
         }
     }
 
@@ -128,7 +212,7 @@ pub fn display_config(config: &Config) {
         println!("  {}", "<none>".bright_black().italic());
     } else {
         for g in &config.ignore.globs {
-            println!("  {} {}", "•".bright_black(), g.to_string_lossy().magenta());
+            println!("  {} {}", "•".bright_black(), g.magenta());
         }
     }
 
@@ -138,12 +222,28 @@ pub fn display_config(config: &Config) {
         key("normalize_names"),
         bool_val(config.naming.normalize_names)
     );
+    println!(
+        "{} {}",
+        key("transliterate"),
+        bool_val(config.naming.transliterate)
+    );
+    println!("{} {}", key("case"), config.naming.case.as_str().white());
+    println!(
+        "{} {}",
+        key("keep_copy_suffix"),
+        bool_val(config.naming.keep_copy_suffix)
+    );
+    println!(
+        "{} {}",
+        key("max_length"),
+        config.naming.max_length().to_string().white()
+    );
 
     section("LAYOUT");
     println!(
         "{} {}",
         key("date_source"),
-        config.layout.date_source.white()
+        config.layout.date_source.as_str().white()
     );
     println!(
         "{} {}",
@@ -171,6 +271,19 @@ pub fn display_config(config: &Config) {
         "false".green().bold()
     };
     println!("{} {}", key("allow_delete"), allow_delete);
+    println!(
+        "{} {}",
+        key("quarantine_retention"),
+        opt_str(config.safety.quarantine_retention.as_deref())
+    );
+    println!("{}", key("protected"));
+    if config.safety.protected.is_empty() {
+        println!("  {}", "<none>".bright_black().italic());
+    } else {
+        for g in &config.safety.protected {
+            println!("  {} {}", "•".bright_black(), g.magenta());
+        }
+    }
 
     section("RULES");
     if config.rules.is_empty() {
@@ -197,6 +310,9 @@ pub fn display_config(config: &Config) {
             key("priority"),
             rule.priority.to_string().cyan().bold()
         );
+        if rule.settle.is_some() {
+            println!("  {} {}", key("settle"), opt_str(rule.settle.as_deref()));
+        }
 
         // MATCH
         println!("  {}", "match".bright_blue().bold());
@@ -204,31 +320,7 @@ pub fn display_config(config: &Config) {
             None => {
                 println!("    {}", "<none>".bright_black().italic());
             }
-            Some(m) => {
-                let any = m.any.unwrap_or(false);
-                println!("    {} {}", key("any"), bool_val(any));
-
-                match m.extensions.as_ref() {
-                    None => println!(
-                        "    {} {}",
-                        key("extensions"),
-                        "<none>".bright_black().italic()
-                    ),
-                    Some(exts) if exts.is_empty() => {
-                        println!(
-                            "    {} {}",
-                            key("extensions"),
-                            "<none>".bright_black().italic()
-                        )
-                    }
-                    Some(exts) => {
-                        println!("    {}", key("extensions"));
-                        for e in exts {
-                            println!("      {} {}", "•".bright_black(), e.magenta());
-                        }
-                    }
-                }
-            }
+            Some(m) => display_match(m, 4, false),
         }
 
         // ACTION
@@ -244,7 +336,21 @@ pub fn display_config(config: &Config) {
                     key("to"),
                     opt_str(Some(&a.to.to_string_lossy())).green()
                 );
+                if a.renames() {
+                    println!("    {} {}", key("name"), opt_str(a.name.as_deref()).green());
+                    println!("    {} {}", key("pattern"), opt_str(a.pattern.as_deref()));
+                    println!(
+                        "    {} {}",
+                        key("replacement"),
+                        opt_str(Some(&a.replacement))
+                    );
+                }
                 println!("    {} {}", key("use_layout"), bool_val(a.use_layout));
+                println!(
+                    "    {} {}",
+                    key("conflict_strategy"),
+                    conflict_val(&a.conflict_strategy)
+                );
             }
         }
     }
@@ -1,2 +1,7 @@
+#[allow(clippy::module_inception)]
 pub mod config;
 pub mod display;
+pub mod patterns;
+pub mod template;
+pub mod units;
+pub mod validate;
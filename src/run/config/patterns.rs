@@ -0,0 +1,24 @@
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+
+// `*` stays inside one path component, `**` crosses directories.
+// Case-insensitive, like extensions: "*.part" must also catch "FILE.PART".
+pub fn build_glob(pattern: &str) -> Result<Glob, String> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("invalid glob {:?}: {}", pattern, e.kind()))
+}
+
+pub fn build_glob_set(patterns: &[String]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(build_glob(pattern)?);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+// lowercase and strip a leading dot so "PDF", ".pdf" and "pdf" are the same extension
+pub fn normalize_extension(extension: &str) -> String {
+    extension.trim_start_matches('.').to_ascii_lowercase()
+}
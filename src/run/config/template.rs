@@ -0,0 +1,153 @@
+use chrono::format::{Item, StrftimeItems};
+use std::path::PathBuf;
+
+// Placeholders accepted in `rules.action.to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Rule,
+    Ext,
+    Stem,
+    Name,
+    // directory of the file relative to its root, empty at the top level
+    Parent,
+    // last component of the root the file was found under
+    Root,
+    Year,
+    Month,
+    Day,
+    // strftime format, "%Y-%m-%d" for a bare {date}
+    Date(String),
+    SizeBucket,
+}
+
+#[derive(Debug, Clone)]
+pub enum Segment {
+    Literal(String),
+    Token(Token),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Template {
+    pub segments: Vec<Segment>,
+    // the last component uses {name}, {stem} or {ext}: the template spells out
+    // the whole file path instead of the directory the file goes into
+    pub names_file: bool,
+}
+
+impl Template {
+    // "{rule}/{ext}/{year}" -> [Token(Rule), "/", Token(Ext), "/", Token(Year)].
+    // "{{" and "}}" stand for literal braces.
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut segments: Vec<Segment> = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => {
+                                return Err(format!("unclosed token {{{} in {:?}", name, source));
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Token(parse_token(&name)?));
+                }
+                '}' => {
+                    return Err(format!(
+                        "unmatched }} in {:?}, write }}}} for a brace",
+                        source
+                    ));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        let names_file = last_component(&segments).iter().any(|segment| {
+            matches!(
+                segment,
+                Segment::Token(Token::Name | Token::Stem | Token::Ext)
+            )
+        });
+        Ok(Self {
+            segments,
+            names_file,
+        })
+    }
+
+    // Leading directories that hold no token, e.g. "by-size" for
+    // "by-size/{size_bucket}". Empty when the template starts with a token.
+    pub fn static_prefix(&self) -> PathBuf {
+        let mut prefix = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => prefix.push_str(text),
+                Segment::Token(_) => {
+                    let cut = prefix.rfind('/').unwrap_or(0);
+                    prefix.truncate(cut);
+                    break;
+                }
+            }
+        }
+        PathBuf::from(prefix)
+    }
+}
+
+fn parse_token(name: &str) -> Result<Token, String> {
+    let token = match name {
+        "rule" => Token::Rule,
+        "ext" => Token::Ext,
+        "stem" => Token::Stem,
+        "name" => Token::Name,
+        "parent" => Token::Parent,
+        "root" => Token::Root,
+        "year" => Token::Year,
+        "month" => Token::Month,
+        "day" => Token::Day,
+        "date" => Token::Date("%Y-%m-%d".to_string()),
+        "size_bucket" => Token::SizeBucket,
+        _ => match name.strip_prefix("date:") {
+            Some(format)
+                if !format.is_empty()
+                    && !StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) =>
+            {
+                Token::Date(format.to_string())
+            }
+            Some(format) => {
+                return Err(format!(
+                    "{:?} in {{{}}} is not a valid strftime format",
+                    format, name
+                ));
+            }
+            None => return Err(format!("unknown token {{{}}}", name)),
+        },
+    };
+    Ok(token)
+}
+
+// segments after the last "/" of the template
+fn last_component(segments: &[Segment]) -> &[Segment] {
+    let start = segments
+        .iter()
+        .rposition(|segment| matches!(segment, Segment::Literal(text) if text.contains('/')))
+        .map_or(0, |index| index + 1);
+    &segments[start..]
+}
@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+// "500MB", "1.5 GiB", "2048" (bytes). KB/MB/GB are decimal, KiB/MiB/GiB binary.
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let (number, unit) = split_number(value)?;
+    let multiplier: f64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "t" | "tb" => 1e12,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return Err(format!("unknown size unit {:?} in {:?}", unit, value)),
+    };
+    Ok((number * multiplier).round() as u64)
+}
+
+// "30d", "12h", "1.5w", "90s". A bare number is read as seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, unit) = split_number(value)?;
+    let seconds: f64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "s" | "sec" | "secs" => 1.0,
+        "m" | "min" | "mins" => 60.0,
+        "h" | "hr" | "hrs" => 3600.0,
+        "d" | "day" | "days" => 86_400.0,
+        "w" | "week" | "weeks" => 604_800.0,
+        _ => return Err(format!("unknown duration unit {:?} in {:?}", unit, value)),
+    };
+    Duration::try_from_secs_f64(number * seconds)
+        .map_err(|_| format!("duration {:?} is out of range", value))
+}
+
+fn split_number(value: &str) -> Result<(f64, &str), String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("expected a number followed by a unit, got {:?}", value))?;
+    Ok((number, unit.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_out_of_range_are_errors() {
+        assert_eq!(parse_duration("1.5h"), Ok(Duration::from_secs(5400)));
+        assert!(parse_duration("100000000000000000000000000000000000d").is_err());
+    }
+}
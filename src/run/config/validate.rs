@@ -0,0 +1,359 @@
+use super::{
+    config::{Action, ActionType, Config, Ignore, Match, Naming},
+    patterns::{build_glob, build_glob_set, normalize_extension},
+    template::Template,
+    units::{parse_duration, parse_size},
+};
+use chrono::format::{Item, StrftimeItems};
+use globset::{GlobSet, GlobSetBuilder};
+use regex::Regex;
+use std::path::{Component, Path};
+
+// room for a short stem next to a long extension
+const MIN_NAME_LENGTH: usize = 16;
+
+// Checks the parts of the config serde cannot, and compiles every pattern once
+// so the scanner and rule engine never rebuild them per file.
+pub fn validate_config(config: &mut Config) -> Result<(), Vec<String>> {
+    let mut errors: Vec<String> = Vec::new();
+
+    compile_ignore(&mut config.ignore, &mut errors);
+    if let Err(error) = check_date_format(&config.layout.date_format) {
+        errors.push(format!("layout.date_format: {}", error));
+    }
+    if let Some(max_length) = config.naming.max_length
+        && !(MIN_NAME_LENGTH..=Naming::MAX_LENGTH).contains(&max_length)
+    {
+        errors.push(format!(
+            "naming.max_length: must be between {} and {}, got {}",
+            MIN_NAME_LENGTH,
+            Naming::MAX_LENGTH,
+            max_length
+        ));
+    }
+    config.safety.retention = compile_unit(
+        config.safety.quarantine_retention.as_deref(),
+        "safety",
+        "quarantine_retention",
+        parse_duration,
+        &mut errors,
+    );
+    if config.safety.retention.is_some() && !config.safety.allow_delete {
+        errors.push(
+            "safety.quarantine_retention: purging the quarantine needs safety.allow_delete = true"
+                .to_string(),
+        );
+    }
+    match build_glob_set(&config.safety.protected) {
+        Ok(globs) => config.safety.protected_globs = globs,
+        Err(error) => errors.push(format!("safety.protected: {}", error)),
+    }
+    config.general.settle_window = compile_unit(
+        config.general.settle.as_deref(),
+        "general",
+        "settle",
+        parse_duration,
+        &mut errors,
+    );
+    for rule in &mut config.rules {
+        rule.settle_window = compile_unit(
+            rule.settle.as_deref(),
+            &format!("rules[{:?}]", rule.name),
+            "settle",
+            parse_duration,
+            &mut errors,
+        );
+        if let Some(rule_match) = rule.r#match.as_mut() {
+            let context = format!("rules[{:?}].match", rule.name);
+            compile_match(rule_match, &context, &mut errors);
+        }
+        if let Some(action) = rule.action.as_mut() {
+            let context = format!("rules[{:?}].action", rule.name);
+            compile_action(action, &context, &mut errors);
+            if rule.enabled && action.r#type == ActionType::Delete && !config.safety.allow_delete {
+                errors.push(format!(
+                    "{}.type: delete needs safety.allow_delete = true (files go to the quarantine, only a purge removes them)",
+                    context
+                ));
+            }
+        }
+    }
+    config.rule_order = order_rules(config);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn compile_action(action: &mut Action, context: &str, errors: &mut Vec<String>) {
+    match Template::parse(&action.to.to_string_lossy()) {
+        Ok(template) => action.template = template,
+        Err(error) => errors.push(format!("{}.to: {}", context, error)),
+    }
+    let needs_to = matches!(action.r#type, ActionType::Copy | ActionType::Move);
+    if needs_to && action.to.as_os_str().is_empty() {
+        errors.push(format!("{}.to: required for copy and move", context));
+    }
+    // `to` parses first, a template naming the file is a rename of its own
+    if action.r#type == ActionType::Rename && !action.renames() && !action.template.names_file {
+        errors.push(format!("{}: rename needs a name or a pattern", context));
+    }
+
+    if let Some(name) = action.name.as_deref() {
+        match Template::parse(name) {
+            Ok(_) if name.contains(['/', '\\']) => {
+                errors.push(format!(
+                    "{}.name: {:?} must be a file name, not a path",
+                    context, name
+                ));
+            }
+            Ok(_) if action.template.names_file => errors.push(format!(
+                "{}.name: `to` already names the file, use one or the other",
+                context
+            )),
+            Ok(template) => action.name_template = Some(template),
+            Err(error) => errors.push(format!("{}.name: {}", context, error)),
+        }
+    }
+    if let Some(pattern) = action.pattern.as_deref() {
+        match Regex::new(pattern) {
+            Ok(regex) => action.name_regex = Some(regex),
+            Err(error) => errors.push(format!(
+                "{}.pattern: invalid regex {:?}: {}",
+                context, pattern, error
+            )),
+        }
+    }
+}
+
+// The formatted date becomes part of a path, so beyond valid strftime it must
+// stay a relative path below the destination.
+fn check_date_format(format: &str) -> Result<(), String> {
+    if format.trim().is_empty() {
+        return Err("must not be empty".to_string());
+    }
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("{:?} is not a valid strftime format", format));
+    }
+    let literal = Path::new(format);
+    if literal.is_absolute()
+        || literal
+            .components()
+            .any(|component| matches!(component, Component::ParentDir))
+    {
+        return Err(format!(
+            "{:?} must be a relative path without \"..\"",
+            format
+        ));
+    }
+    Ok(())
+}
+
+fn compile_ignore(ignore: &mut Ignore, errors: &mut Vec<String>) {
+    match build_glob_set(&ignore.globs) {
+        Ok(globs) => ignore.compiled.globs = globs,
+        Err(error) => errors.push(format!("ignore.globs: {}", error)),
+    }
+
+    let mut dir_globs = GlobSetBuilder::new();
+    let mut dir_glob_sources = Vec::new();
+    for (index, pattern) in ignore.globs.iter().enumerate() {
+        if let Some(dir_pattern) = pattern.strip_suffix("/**")
+            && let Ok(glob) = build_glob(dir_pattern)
+        {
+            dir_globs.add(glob);
+            dir_glob_sources.push(index);
+        }
+    }
+    match dir_globs.build() {
+        Ok(dir_globs) => {
+            ignore.compiled.dir_globs = dir_globs;
+            ignore.compiled.dir_glob_sources = dir_glob_sources;
+        }
+        Err(error) => errors.push(format!("ignore.globs: {}", error)),
+    }
+
+    ignore.compiled.extensions = ignore
+        .extensions
+        .iter()
+        .map(|extension| normalize_extension(extension))
+        .collect();
+}
+
+fn compile_match(rule_match: &mut Match, context: &str, errors: &mut Vec<String>) {
+    // a match without a single criterion could never hold
+    if !has_criteria(rule_match) {
+        errors.push(format!(
+            "{}: must set at least one criterion, use any = true to match every file",
+            context
+        ));
+    }
+    rule_match.compiled.extensions = rule_match
+        .extensions
+        .iter()
+        .flatten()
+        .map(|extension| normalize_extension(extension))
+        .collect();
+
+    rule_match.compiled.name_globs = compile_rule_globs(
+        rule_match.name_globs.as_deref(),
+        context,
+        "name_globs",
+        errors,
+    );
+    rule_match.compiled.path_globs = compile_rule_globs(
+        rule_match.path_globs.as_deref(),
+        context,
+        "path_globs",
+        errors,
+    );
+
+    rule_match.compiled.name_regex = None;
+    if let Some(pattern) = rule_match.name_regex.as_ref() {
+        match Regex::new(pattern) {
+            Ok(regex) => rule_match.compiled.name_regex = Some(regex),
+            Err(error) => errors.push(format!(
+                "{}.name_regex: invalid regex {:?}: {}",
+                context, pattern, error
+            )),
+        }
+    }
+
+    rule_match.compiled.min_size = compile_unit(
+        rule_match.min_size.as_deref(),
+        context,
+        "min_size",
+        parse_size,
+        errors,
+    );
+    rule_match.compiled.max_size = compile_unit(
+        rule_match.max_size.as_deref(),
+        context,
+        "max_size",
+        parse_size,
+        errors,
+    );
+    rule_match.compiled.older_than = compile_unit(
+        rule_match.older_than.as_deref(),
+        context,
+        "older_than",
+        parse_duration,
+        errors,
+    );
+    rule_match.compiled.newer_than = compile_unit(
+        rule_match.newer_than.as_deref(),
+        context,
+        "newer_than",
+        parse_duration,
+        errors,
+    );
+    if let (Some(min), Some(max)) = (rule_match.compiled.min_size, rule_match.compiled.max_size)
+        && min > max
+    {
+        errors.push(format!("{}: min_size is larger than max_size", context));
+    }
+
+    for (field, children) in [
+        ("all", &mut rule_match.all),
+        ("any_of", &mut rule_match.any_of),
+    ] {
+        let Some(children) = children.as_mut() else {
+            continue;
+        };
+        if children.is_empty() {
+            errors.push(format!(
+                "{}.{}: must contain at least one match",
+                context, field
+            ));
+        }
+        for (index, child) in children.iter_mut().enumerate() {
+            let child_context = format!("{}.{}[{}]", context, field, index);
+            compile_match(child, &child_context, errors);
+        }
+    }
+    if let Some(child) = rule_match.not.as_mut() {
+        compile_match(child, &format!("{}.not", context), errors);
+    }
+
+    rule_match.compiled.under = None;
+    if let Some(under) = rule_match.under.as_ref() {
+        let escapes = under
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            errors.push(format!(
+                "{}.under: {:?} must be a path relative to the root without \"..\"",
+                context, under
+            ));
+        } else {
+            rule_match.compiled.under = Some(
+                under
+                    .components()
+                    .filter(|c| matches!(c, Component::Normal(_)))
+                    .collect(),
+            );
+        }
+    }
+}
+
+fn has_criteria(rule_match: &Match) -> bool {
+    rule_match
+        .extensions
+        .as_ref()
+        .is_some_and(|extensions| !extensions.is_empty())
+        || rule_match.any.unwrap_or(false)
+        || rule_match.name_globs.is_some()
+        || rule_match.path_globs.is_some()
+        || rule_match.name_regex.is_some()
+        || rule_match.under.is_some()
+        || rule_match.min_size.is_some()
+        || rule_match.max_size.is_some()
+        || rule_match.older_than.is_some()
+        || rule_match.newer_than.is_some()
+        || rule_match.all.is_some()
+        || rule_match.any_of.is_some()
+        || rule_match.not.is_some()
+}
+
+fn compile_unit<T>(
+    value: Option<&str>,
+    context: &str,
+    field: &str,
+    parse: fn(&str) -> Result<T, String>,
+    errors: &mut Vec<String>,
+) -> Option<T> {
+    match parse(value?) {
+        Ok(parsed) => Some(parsed),
+        Err(error) => {
+            errors.push(format!("{}.{}: {}", context, field, error));
+            None
+        }
+    }
+}
+
+fn compile_rule_globs(
+    patterns: Option<&[String]>,
+    context: &str,
+    field: &str,
+    errors: &mut Vec<String>,
+) -> Option<GlobSet> {
+    let patterns = patterns.filter(|patterns| !patterns.is_empty())?;
+    match build_glob_set(patterns) {
+        Ok(globs) => Some(globs),
+        Err(error) => {
+            errors.push(format!("{}.{}: {}", context, field, error));
+            None
+        }
+    }
+}
+
+fn order_rules(config: &Config) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..config.rules.len())
+        .filter(|index| config.rules[*index].enabled)
+        .collect();
+    // sort_by_key is stable, so equal priorities are evaluated in file order
+    order.sort_by_key(|index| config.rules[*index].priority);
+    order
+}
@@ -1,12 +1,108 @@
-use std::{io::Error, path::PathBuf, time::SystemTime};
+use crate::run::config::config::Action;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::Error,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
 
+#[derive(Debug)]
 pub struct FsEntry {
     pub path: PathBuf,
+    // include root this entry was discovered under
+    pub root: PathBuf,
     pub kind: FileKind,
     pub metadata: Option<EntryMetaData>,
     pub errors: Vec<EntryError>,
+    // set when the entry must not be touched, before any rule is evaluated
+    pub skip: Option<SkipReason>,
+    // winning rule, filled in by the rule engine
+    pub matched: Option<MatchedRule>,
 }
 
+impl FsEntry {
+    pub fn new(path: PathBuf, root: PathBuf) -> Self {
+        Self {
+            path,
+            root,
+            kind: FileKind::Other,
+            metadata: None,
+            errors: Vec::new(),
+            skip: None,
+            matched: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchedRule {
+    pub name: String,
+    pub priority: i64,
+    // what the rule matched on, e.g. `extension "pdf"`
+    pub reason: String,
+    pub action: Option<Action>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum SkipReason {
+    // holds the `ignore.globs` pattern that matched
+    IgnoredGlob(String),
+    Hidden,
+    IgnoredExtension(String),
+    NoRuleMatched,
+    // holds the rule name
+    NoAction(String),
+    AlreadyInPlace,
+    // the path ends in ".." or a root, there is nothing to name a destination after
+    NoFileName,
+    // an earlier run moved or copied the file here, holds its run id
+    AlreadyPlaced(String),
+    // holds the destination that was already taken
+    DestinationTaken(PathBuf),
+    // keep_newest / keep_oldest picked the other file for this destination
+    ConflictLost(PathBuf),
+    // require_within_roots: the resolved destination lies outside every root
+    OutsideRoots(PathBuf),
+    // holds the `safety.protected` pattern that matched
+    Protected(String),
+    // still inside its settle window: unchanged since `since`, needs `window`
+    Settling { since: SystemTime, window: Duration },
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::IgnoredGlob(glob) => write!(f, "ignored by glob {:?}", glob),
+            SkipReason::Hidden => write!(f, "hidden dotfile"),
+            SkipReason::IgnoredExtension(ext) => write!(f, "ignored extension {:?}", ext),
+            SkipReason::NoRuleMatched => write!(f, "no rule matched"),
+            SkipReason::NoAction(rule) => write!(f, "rule {:?} has no action", rule),
+            SkipReason::AlreadyInPlace => write!(f, "already at its destination"),
+            SkipReason::NoFileName => write!(f, "path has no file name"),
+            SkipReason::AlreadyPlaced(run_id) => write!(f, "placed here by run {}", run_id),
+            SkipReason::DestinationTaken(path) => write!(f, "destination {:?} is taken", path),
+            SkipReason::ConflictLost(path) => {
+                write!(f, "another file was kept at {:?}", path)
+            }
+            SkipReason::OutsideRoots(path) => {
+                write!(f, "destination {:?} is outside the roots", path)
+            }
+            SkipReason::Protected(glob) => write!(f, "protected by {:?}", glob),
+            SkipReason::Settling { since, window } => {
+                // the time left depends on when it is shown, the plan itself does not
+                let stable_for = SystemTime::now()
+                    .duration_since(*since)
+                    .unwrap_or(Duration::ZERO);
+                let left = window.saturating_sub(stable_for).as_secs().max(1);
+                write!(f, "pending, still changing (settles in {}s)", left)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum FileKind {
     File,
     Directory,
@@ -14,6 +110,8 @@ pub enum FileKind {
     Other,
 }
 
+#[allow(dead_code)]
+#[derive(Debug)]
 pub struct EntryMetaData {
     pub size_bytes: Option<u64>,
     pub modified: Option<SystemTime>,
@@ -24,6 +122,7 @@ pub struct EntryMetaData {
     pub canonical_path: Option<PathBuf>,
 }
 
+#[derive(Debug)]
 pub struct EntryError {
     pub path: PathBuf,
     pub operation: EntryOp,
@@ -32,6 +131,15 @@ pub struct EntryError {
     pub outcome: Outcome,
 }
 
+impl EntryError {
+    // warnings were skipped on purpose and are picked up by a later run
+    pub fn is_failure(&self) -> bool {
+        !matches!(self.severity, Severity::Warning)
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
 pub enum EntryOp {
     // EntryError
     ReadDir,
@@ -52,14 +160,21 @@ pub enum EntryOp {
     // (if you do ~ expansion / env substitution)
     ParsePath,
     MatchRule, // (optional: if you want rule-engine errors to be structured similarly)
+
+    // (writing state in state_dir: the run journal, settle tracking)
+    Journal,
 }
 
+#[allow(dead_code)]
+#[derive(Debug)]
 pub enum Severity {
     Warning,
     Error,
     Fatal,
 }
 
+#[allow(dead_code)]
+#[derive(Debug)]
 pub enum Outcome {
     Skipped,
     Aborted,
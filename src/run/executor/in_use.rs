@@ -0,0 +1,43 @@
+use std::path::Path;
+
+// The first of `paths` another process holds open, with the pid of one such
+// process. Asked right before every operation: a snapshot taken at the start
+// of a long run would miss a file opened since. Paths must be canonical.
+#[cfg(target_os = "linux")]
+pub fn held_open<'a>(paths: &[&'a Path]) -> Option<(&'a Path, u32)> {
+    use std::fs::{read_dir, read_link};
+
+    if paths.is_empty() {
+        return None;
+    }
+    let processes = read_dir("/proc").ok()?;
+    let own_pid = std::process::id();
+    for process in processes.filter_map(Result::ok) {
+        let Ok(pid) = process.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        if pid == own_pid {
+            continue;
+        }
+        // processes of other users are not readable, nothing to learn there
+        let Ok(descriptors) = read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        for descriptor in descriptors.filter_map(Result::ok) {
+            let Ok(target) = read_link(descriptor.path()) else {
+                continue;
+            };
+            if let Some(path) = paths.iter().find(|path| **path == target) {
+                return Some((path, pid));
+            }
+        }
+    }
+    None
+}
+
+// no portable way to ask, every file counts as closed
+#[cfg(not(target_os = "linux"))]
+pub fn held_open<'a>(paths: &[&'a Path]) -> Option<(&'a Path, u32)> {
+    let _ = paths;
+    None
+}
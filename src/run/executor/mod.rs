@@ -0,0 +1,300 @@
+pub mod in_use;
+pub mod transfer;
+pub mod verify;
+
+use crate::run::{
+    entries::fs_entry::{EntryError, EntryOp, Severity},
+    executor::{
+        in_use::held_open,
+        transfer::{copy_file, hash_file, move_file},
+    },
+    journal::{
+        Journal,
+        record::{JournalRecord, RecordStatus, RunMode},
+    },
+    planner::plan::{Operation, PlanAction},
+    quarantine::{INDEX_FILE, QuarantineEntry, append_entry, forget_entry},
+    scanner::{display::display_error, set_error::parse_errors},
+};
+use colored::*;
+use std::{
+    fs::{canonicalize, create_dir_all},
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+// Executes the operations under a fresh journal in state_dir/runs/<run_id>/.
+pub fn execute_journaled(
+    state_dir: &Path,
+    mode: RunMode,
+    operations: &[Operation],
+) -> Result<Vec<EntryError>, String> {
+    let mut journal = Journal::start(state_dir, mode, operations)?;
+    println!(
+        "{} {}",
+        "Run".bright_yellow().bold(),
+        journal.run_id.white().bold()
+    );
+    let errors = execute_plan(operations, &mut journal);
+    let dir = journal.dir.clone();
+    journal
+        .finish()
+        .map_err(|error| format!("cannot finish the run in {:?}: {}", dir, error))?;
+    Ok(errors)
+}
+
+// Runs every non-skip operation in plan order. A failed operation is recorded
+// and the executor moves on, one bad file never stops the rest. Only a journal
+// that cannot be written stops the run, nothing may happen untraced.
+pub fn execute_plan(operations: &[Operation], journal: &mut Journal) -> Vec<EntryError> {
+    let mut errors: Vec<EntryError> = Vec::new();
+    let mut done = 0;
+
+    for (index, operation) in operations.iter().enumerate() {
+        if operation.action == PlanAction::Skip {
+            continue;
+        }
+        // a file still being written must not move under the writer's feet,
+        // nor may a file someone has open be replaced; it is left alone and
+        // picked up by a later run
+        if let Some(error) = in_use(operation) {
+            display_error(&error);
+            errors.push(error);
+            continue;
+        }
+        // taken before anything moves, undo checks the file against it later
+        let hash = hash_file(&operation.source)
+            .ok()
+            .map(|hash| hash.to_hex().to_string());
+        let pending = JournalRecord::new(
+            &journal.run_id,
+            index,
+            operation,
+            hash.clone(),
+            RecordStatus::Pending,
+        );
+        if let Err(error) = journal.append(&pending) {
+            errors.push(journal_error(journal, error));
+            break;
+        }
+
+        let result = record_quarantine(&journal.run_id, index, operation).and_then(|()| {
+            execute_operation(operation)
+                .inspect_err(|_| forget_quarantine(&journal.run_id, index, operation))
+        });
+        let mut outcome = JournalRecord::new(
+            &journal.run_id,
+            index,
+            operation,
+            hash,
+            RecordStatus::Committed,
+        );
+        if let Err(error) = result.as_ref() {
+            outcome.status = RecordStatus::Failed;
+            outcome.error = Some(
+                error
+                    .source
+                    .as_ref()
+                    .map(|source| source.to_string())
+                    .unwrap_or_else(|| format!("{:?} failed", error.operation)),
+            );
+        }
+        if let Err(error) = journal.append(&outcome) {
+            errors.push(journal_error(journal, error));
+            break;
+        }
+
+        match result {
+            Ok(()) => {
+                done += 1;
+                println!(
+                    "  {} {} {} {}",
+                    format!("{:<7}", operation.action.as_str()).green().bold(),
+                    operation.source.to_string_lossy().white(),
+                    "→".bright_black(),
+                    operation
+                        .destination
+                        .as_deref()
+                        .unwrap_or(Path::new(""))
+                        .to_string_lossy()
+                        .green()
+                );
+            }
+            Err(error) => {
+                display_error(&error);
+                errors.push(error);
+            }
+        }
+    }
+
+    let failed = errors.iter().filter(|error| error.is_failure()).count();
+    println!(
+        "\n{} done, {} skipped, {} failed",
+        done.to_string().green().bold(),
+        (errors.len() - failed).to_string().yellow().bold(),
+        failed.to_string().red().bold()
+    );
+    errors
+}
+
+// the source, and the destination when one is already there to be replaced
+fn in_use(operation: &Operation) -> Option<EntryError> {
+    let source = canonicalize(&operation.source).ok();
+    let destination = operation
+        .destination
+        .as_deref()
+        .and_then(|destination| canonicalize(destination).ok());
+    let paths: Vec<&Path> = source
+        .iter()
+        .chain(destination.iter())
+        .map(|path| path.as_path())
+        .collect();
+    let (path, pid) = held_open(&paths)?;
+    let message = if Some(path) == destination.as_deref() {
+        format!("destination {:?} is open by process {}", path, pid)
+    } else {
+        format!("open by process {}", pid)
+    };
+    Some(parse_errors(
+        &operation.source,
+        entry_op(operation),
+        Some(Error::new(ErrorKind::ResourceBusy, message)),
+        Severity::Warning,
+    ))
+}
+
+fn journal_error(journal: &Journal, error: Error) -> EntryError {
+    let error = parse_errors(
+        &journal.path(),
+        EntryOp::Journal,
+        Some(error),
+        Severity::Fatal,
+    );
+    display_error(&error);
+    error
+}
+
+fn entry_op(operation: &Operation) -> EntryOp {
+    match operation.action {
+        PlanAction::Move => EntryOp::Move,
+        PlanAction::Copy => EntryOp::Copy,
+        PlanAction::Delete | PlanAction::Skip => EntryOp::Delete,
+    }
+}
+
+// indexed before the file moves, see append_entry
+fn record_quarantine(run_id: &str, index: usize, operation: &Operation) -> Result<(), EntryError> {
+    let Some(quarantine) = operation.quarantine.as_deref() else {
+        return Ok(());
+    };
+    let entry = QuarantineEntry::new(run_id, index, quarantine, operation);
+    create_dir_all(quarantine)
+        .and_then(|()| append_entry(quarantine, &entry))
+        .map_err(|error| {
+            parse_errors(
+                &quarantine.join(INDEX_FILE),
+                entry_op(operation),
+                Some(error),
+                Severity::Error,
+            )
+        })
+}
+
+// the file never arrived, its entry must not claim the next file put there;
+// best effort, read_index also prefers the newest entry for a path
+fn forget_quarantine(run_id: &str, index: usize, operation: &Operation) {
+    if let Some(quarantine) = operation.quarantine.as_deref() {
+        let _ = forget_entry(quarantine, &QuarantineEntry::id(run_id, index));
+    }
+}
+
+fn execute_operation(operation: &Operation) -> Result<(), EntryError> {
+    let entry_op = entry_op(operation);
+    let fail =
+        |error: Error| parse_errors(&operation.source, entry_op, Some(error), Severity::Error);
+
+    let Some(destination) = operation.destination.as_deref() else {
+        return Err(fail(Error::new(
+            ErrorKind::InvalidInput,
+            "operation has no destination",
+        )));
+    };
+
+    if let Some(parent) = destination.parent() {
+        create_dir_all(parent).map_err(fail)?;
+    }
+
+    // never clobber unless the planner chose to, conflicts are resolved while planning
+    match operation.action {
+        // a delete is a move into quarantine
+        PlanAction::Move | PlanAction::Delete => {
+            move_file(&operation.source, destination, operation.overwrite).map_err(fail)
+        }
+        PlanAction::Copy => {
+            copy_file(&operation.source, destination, operation.overwrite).map_err(fail)
+        }
+        PlanAction::Skip => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::journal::{latest_records, read_journal};
+    use std::fs::{read_to_string, write};
+
+    #[test]
+    fn every_operation_is_journaled_and_failures_do_not_stop_the_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        let quarantine = dir.path().join("quarantine");
+        create_dir_all(&root).unwrap();
+        write(root.join("a.txt"), "a").unwrap();
+        write(root.join("b.txt"), "b").unwrap();
+        write(root.join("taken.txt"), "taken").unwrap();
+
+        let mut delete = Operation::planned(
+            PlanAction::Delete,
+            root.join("missing.txt"),
+            root.clone(),
+            Some(quarantine.join("missing.txt")),
+        );
+        delete.quarantine = Some(quarantine.clone());
+        let operations = [
+            Operation::planned(
+                PlanAction::Move,
+                root.join("a.txt"),
+                root.clone(),
+                Some(root.join("docs/a.txt")),
+            ),
+            Operation::planned(
+                PlanAction::Move,
+                root.join("b.txt"),
+                root.clone(),
+                Some(root.join("taken.txt")),
+            ),
+            delete,
+        ];
+        let mut journal = Journal::start(dir.path(), RunMode::Run, &operations).unwrap();
+        let errors = execute_plan(&operations, &mut journal);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(read_to_string(root.join("docs/a.txt")).unwrap(), "a");
+        assert_eq!(read_to_string(root.join("taken.txt")).unwrap(), "taken");
+        let statuses: Vec<RecordStatus> = latest_records(read_journal(&journal.dir).unwrap())
+            .into_iter()
+            .map(|record| record.status)
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                RecordStatus::Committed,
+                RecordStatus::Failed,
+                RecordStatus::Failed
+            ]
+        );
+        // the delete never reached the quarantine, nor may its entry
+        let index = read_to_string(quarantine.join(INDEX_FILE)).unwrap();
+        assert!(index.is_empty());
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    fs::{
+        File, FileTimes, OpenOptions, read_link, remove_file, rename, set_permissions,
+        symlink_metadata,
+    },
+    io::{self, Error, ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+// Same filesystem: a plain rename, which keeps mtime and permissions by itself.
+// Across filesystems: a verified copy into place, then the source is removed.
+// A symlink is moved as a link, copying it would copy what it points to.
+pub fn move_file(source: &Path, destination: &Path, overwrite: bool) -> io::Result<()> {
+    check_destination(destination, overwrite)?;
+    match rename(source, destination) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == ErrorKind::CrossesDevices => {
+            move_across(source, destination, overwrite)
+        }
+        Err(error) => Err(error),
+    }
+}
+
+// the source is only removed once the copy is in place
+fn move_across(source: &Path, destination: &Path, overwrite: bool) -> io::Result<()> {
+    if symlink_metadata(source)?.is_symlink() {
+        copy_link(source, destination)?;
+    } else {
+        copy_file(source, destination, overwrite)?;
+    }
+    remove_file(source)
+}
+
+// the same link target under the destination, through a temp name like a copy
+fn copy_link(source: &Path, destination: &Path) -> io::Result<()> {
+    let target = read_link(source)?;
+    let temp = temp_path(destination);
+    let _ = remove_file(&temp);
+    let result = make_link(&target, &temp).and_then(|()| rename(&temp, destination));
+    if result.is_err() {
+        let _ = remove_file(&temp);
+    }
+    result?;
+    sync_parent(destination);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_link(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn make_link(_target: &Path, link: &Path) -> io::Result<()> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        format!(
+            "cannot recreate the symlink {:?} on another filesystem",
+            link
+        ),
+    ))
+}
+
+// Copies through a temp file next to the destination, so a half written file
+// never shows up under the final name. The copy is fsync'd and compared
+// against the source (size and blake3) before it is renamed into place.
+pub fn copy_file(source: &Path, destination: &Path, overwrite: bool) -> io::Result<()> {
+    check_destination(destination, overwrite)?;
+    let temp = temp_path(destination);
+    // left over from an interrupted copy, it only ever holds a partial file
+    let _ = remove_file(&temp);
+    let result = copy_verified(source, &temp).and_then(|()| rename(&temp, destination));
+    if result.is_err() {
+        let _ = remove_file(&temp);
+    }
+    result?;
+    sync_parent(destination);
+    Ok(())
+}
+
+fn copy_verified(source: &Path, temp: &Path) -> io::Result<()> {
+    let mut reader = File::open(source)?;
+    let metadata = reader.metadata()?;
+    let mut writer = OpenOptions::new().write(true).create_new(true).open(temp)?;
+
+    let (copied, source_hash) = copy_hashed(&mut reader, &mut writer)?;
+    writer.sync_all()?;
+    if copied != metadata.len() {
+        return Err(Error::other(format!(
+            "copied {} B but the source has {} B",
+            copied,
+            metadata.len()
+        )));
+    }
+
+    // read back what actually reached the disk
+    let (written, written_hash) = copy_hashed(&mut File::open(temp)?, &mut io::sink())?;
+    if written != copied || written_hash != source_hash {
+        return Err(Error::other(
+            "checksum of the copy does not match the source",
+        ));
+    }
+
+    let mut times = FileTimes::new();
+    if let Ok(modified) = metadata.modified() {
+        times = times.set_modified(modified);
+    }
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    writer.set_times(times)?;
+    set_permissions(temp, metadata.permissions())
+}
+
+pub fn hash_file(path: &Path) -> io::Result<blake3::Hash> {
+    let (_, hash) = copy_hashed(&mut File::open(path)?, &mut io::sink())?;
+    Ok(hash)
+}
+
+fn copy_hashed(reader: &mut impl Read, writer: &mut impl Write) -> io::Result<(u64, blake3::Hash)> {
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut total: u64 = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+        total += read as u64;
+    }
+    Ok((total, hasher.finalize()))
+}
+
+fn check_destination(destination: &Path, overwrite: bool) -> io::Result<()> {
+    match symlink_metadata(destination) {
+        Ok(metadata) if metadata.is_dir() => Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("destination {:?} is a directory", destination),
+        )),
+        Ok(_) if !overwrite => Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!("destination {:?} already exists", destination),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// ".report.pdf.neatd-tmp" in the destination directory, hidden and on the right filesystem
+pub fn temp_path(destination: &Path) -> PathBuf {
+    let name = destination
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    destination.with_file_name(format!(".{}.neatd-tmp", name))
+}
+
+// makes the rename itself durable, best effort since not every platform allows it
+fn sync_parent(destination: &Path) {
+    if let Some(parent) = destination.parent()
+        && let Ok(directory) = File::open(parent)
+    {
+        let _ = directory.sync_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        fs::{read, read_to_string, write},
+        time::{Duration, SystemTime},
+    };
+
+    #[test]
+    fn copy_keeps_content_and_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("a.bin");
+        let destination = dir.path().join("b.bin");
+        let content: Vec<u8> = (0..200_000u32).map(|n| (n % 251) as u8).collect();
+        write(&source, &content).unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        copy_file(&source, &destination, false).unwrap();
+        assert_eq!(read(&destination).unwrap(), content);
+        assert_eq!(hash_file(&destination).unwrap(), blake3::hash(&content));
+        assert_eq!(
+            destination.metadata().unwrap().modified().unwrap(),
+            modified
+        );
+        assert!(!temp_path(&destination).exists());
+    }
+
+    #[test]
+    fn failed_copy_leaves_no_temp_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let destination = dir.path().join("b.bin");
+        // left over from an interrupted copy
+        write(temp_path(&destination), "partial").unwrap();
+
+        assert!(copy_file(&dir.path().join("missing"), &destination, false).is_err());
+        assert!(!temp_path(&destination).exists());
+        assert!(!destination.exists());
+    }
+
+    #[test]
+    fn existing_destination_is_kept_unless_overwriting() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("a.txt");
+        let destination = dir.path().join("b.txt");
+        write(&source, "new").unwrap();
+        write(&destination, "old").unwrap();
+
+        let error = move_file(&source, &destination, false).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::AlreadyExists);
+        assert_eq!(read_to_string(&destination).unwrap(), "old");
+
+        move_file(&source, &destination, true).unwrap();
+        assert_eq!(read_to_string(&destination).unwrap(), "new");
+        assert!(!source.exists());
+    }
+
+    // what move_file falls back to on EXDEV, a real second filesystem is not
+    // something a test can count on
+    #[test]
+    fn move_across_copies_then_removes_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("a.txt");
+        let destination = dir.path().join("b.txt");
+        write(&source, "content").unwrap();
+
+        move_across(&source, &destination, false).unwrap();
+        assert_eq!(read_to_string(&destination).unwrap(), "content");
+        assert!(!source.exists());
+    }
+
+    #[test]
+    fn move_across_fails_before_touching_the_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("a.txt");
+        let destination = dir.path().join("b.txt");
+        write(&source, "content").unwrap();
+        write(&destination, "taken").unwrap();
+
+        assert!(move_across(&source, &destination, false).is_err());
+        assert_eq!(read_to_string(&source).unwrap(), "content");
+        assert_eq!(read_to_string(&destination).unwrap(), "taken");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_moves_across_as_a_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target.txt");
+        let link = dir.path().join("link");
+        let destination = dir.path().join("moved");
+        write(&target, "target").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        move_across(&link, &destination, false).unwrap();
+        assert!(symlink_metadata(&destination).unwrap().is_symlink());
+        assert_eq!(read_link(&destination).unwrap(), target);
+        assert!(symlink_metadata(&link).is_err());
+        assert_eq!(read_to_string(&target).unwrap(), "target");
+    }
+}
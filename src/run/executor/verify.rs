@@ -0,0 +1,55 @@
+use crate::run::planner::plan::{Operation, PlanAction};
+use std::fs::symlink_metadata;
+
+// The config may have changed since a plan file was saved, its deletes are
+// checked against the current one.
+pub fn verify_deletes(operations: &[Operation], allow_delete: bool) -> Vec<String> {
+    if allow_delete {
+        return Vec::new();
+    }
+    operations
+        .iter()
+        .filter(|operation| operation.action == PlanAction::Delete)
+        .map(|operation| {
+            format!(
+                "{}: delete needs safety.allow_delete = true",
+                operation.source.to_string_lossy()
+            )
+        })
+        .collect()
+}
+
+// A saved plan is only valid for the tree it was built from: every source must
+// still exist with the size and mtime recorded at planning time.
+pub fn verify_sources(operations: &[Operation]) -> Vec<String> {
+    let mut stale: Vec<String> = Vec::new();
+    for operation in operations {
+        if operation.action == PlanAction::Skip {
+            continue;
+        }
+        let source = operation.source.to_string_lossy();
+        let metadata = match symlink_metadata(&operation.source) {
+            Ok(metadata) => metadata,
+            Err(error) => {
+                stale.push(format!("{}: {}", source, error));
+                continue;
+            }
+        };
+        if operation
+            .source_size
+            .is_some_and(|size| size != metadata.len())
+        {
+            stale.push(format!(
+                "{}: size changed from {} B to {} B",
+                source,
+                operation.source_size.unwrap_or_default(),
+                metadata.len()
+            ));
+        } else if operation.source_modified.is_some()
+            && operation.source_modified != metadata.modified().ok()
+        {
+            stale.push(format!("{}: modified since the plan was made", source));
+        }
+    }
+    stale
+}
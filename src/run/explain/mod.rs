@@ -0,0 +1,203 @@
+use crate::run::{
+    config::config::{Config, Rule, resolve_path},
+    entries::fs_entry::{FileKind, FsEntry, MatchedRule, SkipReason},
+    planner::{build_operation, check_operations, plan::PlanAction},
+    policies::{
+        config_policy::ConfigPolicy, effective_policy::EffectivePolicy, setup_policy_for_walk,
+        should_process,
+    },
+    scanner::{
+        ignore_filter::check_ignore, process_file::evaluate_rule, set_kind::set_entry_kind,
+        set_metadata::set_entry_metadata,
+    },
+};
+use colored::*;
+use std::{
+    fs::symlink_metadata,
+    path::{Path, PathBuf},
+};
+
+fn section(title: &str) {
+    println!("\n{}", title.bright_blue().bold());
+    println!("{}", "─".repeat(title.len()).bright_black());
+}
+
+fn pass(message: String) {
+    println!("  {} {}", "✓".green().bold(), message);
+}
+
+fn fail(message: String) {
+    println!("  {} {}", "✗".red().bold(), message);
+}
+
+fn note(message: String) {
+    println!("  {} {}", "·".bright_black(), message.bright_black());
+}
+
+// Walks one path through the same stages a scan would (policy, ignore, rules)
+// and prints the decision taken at every stage.
+pub fn explain_path(config: &Config, base_dir: &Path, file: &Path) {
+    let path = ConfigPolicy::lexical_normalize(&base_dir.join(file));
+    let effective_policy: EffectivePolicy = setup_policy_for_walk(config, base_dir);
+
+    println!(
+        "\n{} {}",
+        "EXPLAIN".bright_yellow().bold(),
+        path.to_string_lossy().white().bold()
+    );
+
+    section("ROOTS");
+    let Some(root) = find_root(&effective_policy, &path) else {
+        fail("not inside any include root".to_string());
+        for root in &effective_policy.effective_includes {
+            note(format!("include root {}", root.to_string_lossy()));
+        }
+        return;
+    };
+    pass(format!("inside include root {}", root.to_string_lossy()));
+
+    if !should_process(&effective_policy, &path) {
+        let exclude = path
+            .ancestors()
+            .find(|ancestor| effective_policy.effective_excludes_hash.contains(*ancestor))
+            .unwrap_or(&path);
+        fail(format!("excluded by {}", exclude.to_string_lossy()));
+        return;
+    }
+    pass("not under any exclude root".to_string());
+
+    section("IGNORE");
+    let relative = path.strip_prefix(&root).unwrap_or(&path).to_path_buf();
+    if !config.general.recursive && relative.components().count() > 1 {
+        fail("general.recursive is false and the file is not directly in the root".to_string());
+        return;
+    }
+    // parent directories first: an ignored directory is never descended into
+    let parents: Vec<&Path> = relative
+        .ancestors()
+        .skip(1)
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .collect();
+    for parent in parents.into_iter().rev() {
+        if let Some(reason) = check_ignore(&config.ignore, parent, true) {
+            fail(format!(
+                "directory {} skipped: {}",
+                parent.to_string_lossy(),
+                reason
+            ));
+            return;
+        }
+    }
+
+    let mut entry = FsEntry::new(path.clone(), root.clone());
+    match symlink_metadata(&path) {
+        Ok(metadata) => set_entry_kind(&mut entry, &metadata.file_type()),
+        Err(error) => note(format!(
+            "cannot read the file ({}), metadata checks will fail",
+            error
+        )),
+    }
+    if let Some(reason) = check_ignore(&config.ignore, &relative, false) {
+        fail(format!("skipped: {}", reason));
+        return;
+    }
+    pass("no ignore glob, hidden or extension filter applies".to_string());
+    if symlink_metadata(&path).is_ok() && !matches!(entry.kind, FileKind::File | FileKind::Symlink)
+    {
+        fail("not a file or a symlink, rules are never applied to it".to_string());
+        return;
+    }
+    set_entry_metadata(&mut entry);
+
+    section("RULES");
+    let mut winner: Option<(&Rule, String)> = None;
+    for rule in config.rules_by_priority() {
+        let label = format!("[{}] {}", rule.priority, rule.name.bold());
+        if let Some((winner, _)) = winner.as_ref() {
+            note(format!(
+                "[{}] {}: not evaluated, {} already matched",
+                rule.priority, rule.name, winner.name
+            ));
+            continue;
+        }
+        match evaluate_rule(rule, &entry) {
+            Ok(reason) => {
+                pass(format!("{} matched: {}", label, reason));
+                winner = Some((rule, reason));
+            }
+            Err(reason) => fail(format!("{} {}", label, reason)),
+        }
+    }
+    // listed too, so it is visible that they were not used
+    for rule in config.rules.iter().filter(|rule| !rule.enabled) {
+        note(format!("[{}] {}: disabled", rule.priority, rule.name));
+    }
+
+    section("DECISION");
+    let Some((rule, reason)) = winner else {
+        fail("no rule matched, the file is left in place".to_string());
+        return;
+    };
+    let Some(action) = rule.action.as_ref() else {
+        fail(format!(
+            "rule {} has no action, the file is left in place",
+            rule.name
+        ));
+        return;
+    };
+    pass(format!(
+        "{} by rule {}",
+        format!("{:?}", action.r#type).to_lowercase(),
+        rule.name
+    ));
+
+    // the planner's own operation and checks, only collisions with other
+    // files are left out since they depend on the whole plan
+    entry.matched = Some(MatchedRule {
+        name: rule.name.clone(),
+        priority: rule.priority,
+        reason,
+        action: Some(action.clone()),
+    });
+    let quarantine = resolve_path(base_dir, &config.paths.quarantine);
+    let mut operations = [build_operation(config, &quarantine, &entry)];
+    for warning in check_operations(config, base_dir, &mut operations) {
+        if let Some(error) = warning.source.as_ref() {
+            note(error.to_string());
+        }
+    }
+    let [operation] = operations;
+    match operation.skip_reason.as_ref() {
+        Some(reason @ SkipReason::Settling { .. }) => {
+            fail(format!("{}, a later run picks it up", reason));
+            return;
+        }
+        Some(reason) => {
+            fail(format!("{}, the file is left in place", reason));
+            return;
+        }
+        None => {}
+    }
+    let destination = operation
+        .destination
+        .as_deref()
+        .unwrap_or(Path::new(""))
+        .to_string_lossy();
+    if operation.action == PlanAction::Delete {
+        pass(format!("quarantined as {}", destination));
+    } else if action.use_layout {
+        pass(format!(
+            "destination {} (date layout from {})",
+            destination,
+            config.layout.date_source.as_str()
+        ));
+    } else {
+        pass(format!("destination {}", destination));
+    }
+}
+
+fn find_root(effective_policy: &EffectivePolicy, path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|ancestor| effective_policy.effective_includes_hash.contains(*ancestor))
+        .map(Path::to_path_buf)
+}
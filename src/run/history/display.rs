@@ -0,0 +1,144 @@
+use super::{OperationStatus, RunDetail, RunSummary};
+use crate::run::journal::record::{RunInfo, RunMode};
+use chrono::DateTime;
+use colored::*;
+use serde::Serialize;
+
+fn mode_val(m: &RunMode) -> ColoredString {
+    // pad first, then color (better alignment)
+    match m {
+        RunMode::Run => format!("{:<6}", "run").green().bold(),
+        RunMode::Apply => format!("{:<6}", "apply").cyan().bold(),
+        RunMode::Undo => format!("{:<6}", "undo").yellow().bold(),
+    }
+}
+
+fn status_val(s: &OperationStatus) -> ColoredString {
+    let padded = format!("{:<10}", s.as_str());
+    match s {
+        OperationStatus::Committed => padded.green().bold(),
+        OperationStatus::Failed => padded.red().bold(),
+        OperationStatus::Pending => padded.yellow().bold(),
+        OperationStatus::Undone => padded.cyan(),
+        OperationStatus::NotRun | OperationStatus::Skipped => padded.bright_black(),
+    }
+}
+
+// run timestamps are RFC 3339, shown without the fraction and offset
+fn time_val(timestamp: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+fn finished_val(info: &RunInfo) -> ColoredString {
+    match info.finished_at.as_deref() {
+        Some(finished_at) => time_val(finished_at).normal(),
+        None => "interrupted".red().bold(),
+    }
+}
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
+
+pub fn display_runs(runs: &[RunSummary]) {
+    println!("\n{}", "HISTORY".bright_yellow().bold());
+    println!("{}", "═══════".bright_black());
+    if runs.is_empty() {
+        println!("{}", "no runs recorded yet".bright_black().italic());
+        return;
+    }
+
+    for run in runs {
+        let roots: Vec<String> = run
+            .info
+            .roots
+            .iter()
+            .map(|root| root.to_string_lossy().into_owned())
+            .collect();
+        println!(
+            "\n{} {} {} {} {}",
+            run.info.run_id.white().bold(),
+            mode_val(&run.info.mode),
+            time_val(&run.info.started_at),
+            "→".bright_black(),
+            finished_val(&run.info)
+        );
+        println!("  {}", roots.join(", ").bright_blue());
+        let counts: Vec<String> = run
+            .actions
+            .iter()
+            .map(|(action, count)| format!("{} {}", count, action))
+            .collect();
+        print!(
+            "  {}, {} errors",
+            counts.join(", "),
+            run.errors.to_string().red().bold()
+        );
+        if run.pending > 0 {
+            print!(", {} pending", run.pending.to_string().yellow().bold());
+        }
+        if run.undone > 0 {
+            print!(", {} undone", run.undone.to_string().cyan().bold());
+        }
+        println!();
+    }
+}
+
+pub fn display_run(detail: &RunDetail) {
+    let info = &detail.info;
+    let operations = &detail.operations;
+    println!(
+        "\n{} {} {}",
+        "RUN".bright_yellow().bold(),
+        info.run_id.white().bold(),
+        mode_val(&info.mode)
+    );
+    println!(
+        "{} {} {}",
+        time_val(&info.started_at),
+        "→".bright_black(),
+        finished_val(info)
+    );
+    println!();
+
+    for operation in operations {
+        let source = operation.source.to_string_lossy();
+        let action = format!("{:<7}", operation.action.as_str());
+        let rule = operation
+            .rule
+            .as_deref()
+            .map(|rule| format!(" [{}]", rule))
+            .unwrap_or_default();
+        match operation.destination.as_ref() {
+            Some(destination) => println!(
+                "  {} {} {} {} {}{}",
+                status_val(&operation.status),
+                action.bold(),
+                source.white(),
+                "→".bright_black(),
+                destination.to_string_lossy().green(),
+                rule.bright_black()
+            ),
+            None => println!(
+                "  {} {} {} {}",
+                status_val(&operation.status),
+                action.bright_black(),
+                source.white(),
+                operation
+                    .skip_reason
+                    .as_ref()
+                    .map(|reason| reason.to_string())
+                    .unwrap_or_default()
+                    .bright_black()
+            ),
+        }
+        if let Some(error) = operation.error.as_deref() {
+            println!("    {} {}", "error".red(), error);
+        }
+    }
+    println!("\n{} operations", operations.len().to_string().bold());
+}
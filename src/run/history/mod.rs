@@ -0,0 +1,253 @@
+pub mod display;
+
+use crate::run::{
+    config::patterns::build_glob,
+    entries::fs_entry::SkipReason,
+    journal::{
+        PLAN_FILE, latest_records, list_runs, read_journal, read_run_info,
+        record::{RecordStatus, RunInfo},
+        runs_dir,
+    },
+    planner::{plan::PlanAction, plan_file::load_plan},
+};
+use colored::*;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+#[derive(Serialize)]
+pub struct RunSummary {
+    #[serde(flatten)]
+    pub info: RunInfo,
+    // committed operations per action
+    pub actions: BTreeMap<&'static str, usize>,
+    pub errors: usize,
+    // operations still pending, only ever non-zero for an interrupted run
+    pub pending: usize,
+    // committed operations an undo reversed since
+    pub undone: usize,
+}
+
+#[derive(Serialize)]
+pub struct RunDetail {
+    #[serde(flatten)]
+    pub info: RunInfo,
+    pub operations: Vec<OperationView>,
+}
+
+#[derive(Serialize)]
+pub struct OperationView {
+    pub index: usize,
+    pub action: PlanAction,
+    pub status: OperationStatus,
+    pub source: PathBuf,
+    pub destination: Option<PathBuf>,
+    pub rule: Option<String>,
+    pub skip_reason: Option<SkipReason>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationStatus {
+    Committed,
+    Failed,
+    Pending,
+    Undone,
+    // planned, but the run ended before reaching it
+    NotRun,
+    Skipped,
+}
+
+impl OperationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationStatus::Committed => "committed",
+            OperationStatus::Failed => "failed",
+            OperationStatus::Pending => "pending",
+            OperationStatus::Undone => "undone",
+            OperationStatus::NotRun => "not_run",
+            OperationStatus::Skipped => "skipped",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct HistoryFilter {
+    pub rule: Option<String>,
+    pub action: Option<String>,
+    pub status: Option<String>,
+    // matched against the source path relative to its root
+    pub glob: Option<String>,
+}
+
+// Every recorded run, oldest first. A run that cannot be read is reported on
+// stderr and left out, one damaged directory must not hide all the others.
+pub fn summarize_runs(state_dir: &Path) -> Result<Vec<RunSummary>, String> {
+    let mut summaries: Vec<RunSummary> = Vec::new();
+    for run_id in list_runs(state_dir)? {
+        match summarize_run(&runs_dir(state_dir).join(&run_id)) {
+            Ok(summary) => summaries.push(summary),
+            Err(error) => eprintln!("  {} {}", "warning".yellow().bold(), error),
+        }
+    }
+    Ok(summaries)
+}
+
+fn summarize_run(run_dir: &Path) -> Result<RunSummary, String> {
+    let mut summary = RunSummary {
+        info: read_run_info(run_dir)?,
+        actions: BTreeMap::from([("copy", 0), ("delete", 0), ("move", 0)]),
+        errors: 0,
+        pending: 0,
+        undone: 0,
+    };
+    for record in latest_records(read_journal(run_dir)?) {
+        match record.status {
+            RecordStatus::Committed => {
+                *summary.actions.entry(record.action.as_str()).or_default() += 1;
+            }
+            RecordStatus::Failed => summary.errors += 1,
+            RecordStatus::Pending => summary.pending += 1,
+            RecordStatus::Undone => summary.undone += 1,
+        }
+    }
+    Ok(summary)
+}
+
+// The plan of the run joined with the last journal record of every operation.
+pub fn run_operations(
+    state_dir: &Path,
+    run_id: &str,
+    filter: &HistoryFilter,
+) -> Result<RunDetail, String> {
+    let run_dir = runs_dir(state_dir).join(run_id);
+    if !run_dir.is_dir() {
+        return Err(format!(
+            "run {} not found in {:?}",
+            run_id,
+            runs_dir(state_dir)
+        ));
+    }
+    let info = read_run_info(&run_dir)?;
+    let plan = load_plan(&run_dir.join(PLAN_FILE))?;
+    let records: HashMap<usize, _> = latest_records(read_journal(&run_dir)?)
+        .into_iter()
+        .map(|record| (record.index, record))
+        .collect();
+    let glob = match filter.glob.as_deref() {
+        Some(pattern) => Some(build_glob(pattern)?.compile_matcher()),
+        None => None,
+    };
+
+    let mut views: Vec<OperationView> = Vec::new();
+    for (index, operation) in plan.operations.into_iter().enumerate() {
+        let record = records.get(&index);
+        let status = match record.map(|record| record.status) {
+            Some(RecordStatus::Committed) => OperationStatus::Committed,
+            Some(RecordStatus::Failed) => OperationStatus::Failed,
+            Some(RecordStatus::Pending) => OperationStatus::Pending,
+            Some(RecordStatus::Undone) => OperationStatus::Undone,
+            None if operation.action == PlanAction::Skip => OperationStatus::Skipped,
+            None => OperationStatus::NotRun,
+        };
+
+        let relative = operation
+            .source
+            .strip_prefix(&operation.root)
+            .unwrap_or(&operation.source);
+        let keep = filter
+            .rule
+            .as_ref()
+            .is_none_or(|rule| operation.rule.as_ref() == Some(rule))
+            && filter
+                .action
+                .as_deref()
+                .is_none_or(|action| operation.action.as_str() == action)
+            && filter
+                .status
+                .as_deref()
+                .is_none_or(|wanted| status.as_str() == wanted)
+            && glob.as_ref().is_none_or(|glob| glob.is_match(relative));
+        if !keep {
+            continue;
+        }
+
+        views.push(OperationView {
+            index,
+            action: operation.action,
+            status,
+            source: operation.source,
+            destination: operation.destination,
+            rule: operation.rule,
+            skip_reason: operation.skip_reason,
+            error: record.and_then(|record| record.error.clone()),
+        });
+    }
+    Ok(RunDetail {
+        info,
+        operations: views,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::{
+        journal::{Journal, record::JournalRecord, record::RunMode},
+        planner::plan::Operation,
+    };
+
+    #[test]
+    fn undone_operations_can_be_filtered() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        let operations = [
+            Operation::planned(
+                PlanAction::Move,
+                root.join("a.pdf"),
+                root.clone(),
+                Some(root.join("docs/a.pdf")),
+            ),
+            Operation::planned(
+                PlanAction::Move,
+                root.join("b.pdf"),
+                root.clone(),
+                Some(root.join("docs/b.pdf")),
+            ),
+        ];
+        let mut journal = Journal::start(dir.path(), RunMode::Run, &operations).unwrap();
+        for (index, operation) in operations.iter().enumerate() {
+            let record = JournalRecord::new(
+                &journal.run_id,
+                index,
+                operation,
+                None,
+                RecordStatus::Committed,
+            );
+            journal.append(&record).unwrap();
+        }
+        let undone = JournalRecord::new(
+            &journal.run_id,
+            1,
+            &operations[1],
+            None,
+            RecordStatus::Undone,
+        );
+        journal.append(&undone).unwrap();
+
+        let filter = HistoryFilter {
+            status: Some("undone".to_string()),
+            ..HistoryFilter::default()
+        };
+        let detail = run_operations(dir.path(), &journal.run_id, &filter).unwrap();
+        assert_eq!(detail.operations.len(), 1);
+        assert_eq!(detail.operations[0].index, 1);
+        assert_eq!(detail.operations[0].status, OperationStatus::Undone);
+
+        let summary = summarize_run(&journal.dir).unwrap();
+        assert_eq!((summary.actions["move"], summary.undone), (1, 1));
+    }
+}
@@ -0,0 +1,297 @@
+pub mod record;
+
+use crate::run::{
+    journal::record::{JournalRecord, RunInfo, RunMode},
+    planner::{placed::record_placements, plan::Operation, plan_file::save_plan},
+};
+use chrono::Local;
+use std::{
+    collections::BTreeMap,
+    fs::{
+        File, OpenOptions, create_dir, create_dir_all, read, read_dir, read_to_string, rename,
+        write,
+    },
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+pub const JOURNAL_FILE: &str = "journal.jsonl";
+pub const PLAN_FILE: &str = "plan.json";
+pub const RUN_FILE: &str = "run.json";
+
+// state_dir/runs/<run_id>/ holds run.json, plan.json and the append-only journal.jsonl
+pub struct Journal {
+    pub run_id: String,
+    pub dir: PathBuf,
+    state_dir: PathBuf,
+    info: RunInfo,
+    file: File,
+}
+
+impl Journal {
+    pub fn start(
+        state_dir: &Path,
+        mode: RunMode,
+        operations: &[Operation],
+    ) -> Result<Self, String> {
+        let now = Local::now();
+        // sorts by start time, the milliseconds keep back to back runs apart
+        let run_id = now.format("%Y%m%d-%H%M%S-%3f").to_string();
+        let dir = runs_dir(state_dir).join(&run_id);
+        let fail = |error: io::Error| format!("cannot start the journal in {:?}: {}", dir, error);
+
+        create_dir_all(runs_dir(state_dir)).map_err(fail)?;
+        create_dir(&dir).map_err(fail)?;
+
+        let mut roots: Vec<PathBuf> = Vec::new();
+        for operation in operations {
+            if !roots.contains(&operation.root) {
+                roots.push(operation.root.clone());
+            }
+        }
+        let info = RunInfo {
+            run_id: run_id.clone(),
+            started_at: now.to_rfc3339(),
+            finished_at: None,
+            mode,
+            roots,
+        };
+        save_plan(operations, &dir.join(PLAN_FILE))?;
+        let file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(dir.join(JOURNAL_FILE))
+            .map_err(fail)?;
+        // run.json last, a directory without it is not listed as a run
+        write_run_info(&dir, &info).map_err(fail)?;
+        sync_dir(&dir);
+
+        Ok(Self {
+            run_id,
+            dir,
+            state_dir: state_dir.to_path_buf(),
+            info,
+            file,
+        })
+    }
+
+    // Continues the journal of an interrupted run. A torn last line is cut off
+    // first, otherwise the next record would be glued onto it.
+    pub fn reopen(state_dir: &Path, run_id: &str) -> Result<Self, String> {
+        let dir = runs_dir(state_dir).join(run_id);
+        let info = read_run_info(&dir)?;
+        let path = dir.join(JOURNAL_FILE);
+        let fail = |error: io::Error| format!("cannot reopen {:?}: {}", path, error);
+
+        let content = read(&path).map_err(fail)?;
+        let complete = content
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |newline| newline + 1);
+        let file = OpenOptions::new().append(true).open(&path).map_err(fail)?;
+        if complete < content.len() {
+            file.set_len(complete as u64).map_err(fail)?;
+        }
+
+        Ok(Self {
+            run_id: run_id.to_string(),
+            dir,
+            state_dir: state_dir.to_path_buf(),
+            info,
+            file,
+        })
+    }
+
+    pub fn mode(&self) -> RunMode {
+        self.info.mode
+    }
+
+    // One write per line and an fsync after it: a kill can at worst leave a
+    // torn last line, every line before it is complete and on disk.
+    pub fn append(&mut self, record: &JournalRecord) -> io::Result<()> {
+        let mut line = serde_json::to_string(record).map_err(io::Error::other)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.sync_data()
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(JOURNAL_FILE)
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.info.finished_at = Some(Local::now().to_rfc3339());
+        write_run_info(&self.dir, &self.info)?;
+        // best effort, the next plan reads this run's journal instead
+        let _ = record_placements(&self.state_dir);
+        Ok(())
+    }
+}
+
+pub fn runs_dir(state_dir: &Path) -> PathBuf {
+    state_dir.join("runs")
+}
+
+// run ids in start order, oldest first
+pub fn list_runs(state_dir: &Path) -> Result<Vec<String>, String> {
+    let dir = runs_dir(state_dir);
+    let entries = match read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("cannot read {:?}: {}", dir, error)),
+    };
+    let mut run_ids: Vec<String> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().join(RUN_FILE).is_file())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    run_ids.sort();
+    Ok(run_ids)
+}
+
+pub fn read_run_info(run_dir: &Path) -> Result<RunInfo, String> {
+    let path = run_dir.join(RUN_FILE);
+    let content = read_to_string(&path).map_err(|e| format!("cannot read {:?}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("{:?} is not a valid run file: {}", path, e))
+}
+
+// A torn last line is what a kill mid-append leaves behind, it is dropped.
+// A broken line anywhere else means the journal was damaged.
+pub fn read_journal(run_dir: &Path) -> Result<Vec<JournalRecord>, String> {
+    let path = run_dir.join(JOURNAL_FILE);
+    let content = read_to_string(&path).map_err(|e| format!("cannot read {:?}: {}", path, e))?;
+    let lines: Vec<&str> = content.lines().collect();
+    let mut records: Vec<JournalRecord> = Vec::new();
+    for (number, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(_) if number + 1 == lines.len() && !content.ends_with('\n') => break,
+            Err(error) => {
+                return Err(format!("{:?} line {}: {}", path, number + 1, error));
+            }
+        }
+    }
+    Ok(records)
+}
+
+// the last record of every operation, ordered by plan index
+pub fn latest_records(records: Vec<JournalRecord>) -> Vec<JournalRecord> {
+    let mut latest: BTreeMap<usize, JournalRecord> = BTreeMap::new();
+    for record in records {
+        latest.insert(record.index, record);
+    }
+    latest.into_values().collect()
+}
+
+// written aside and renamed over, a kill never leaves half a run.json behind
+fn write_run_info(dir: &Path, info: &RunInfo) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(info).map_err(io::Error::other)?;
+    let temp = dir.join(format!(".{}.tmp", RUN_FILE));
+    write(&temp, json + "\n")?;
+    rename(&temp, dir.join(RUN_FILE))
+}
+
+fn sync_dir(dir: &Path) {
+    if let Ok(directory) = File::open(dir) {
+        let _ = directory.sync_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::{
+        journal::record::RecordStatus,
+        planner::plan::{Operation, PlanAction},
+    };
+    use std::fs::read_to_string;
+
+    fn operations(root: &Path) -> Vec<Operation> {
+        ["a.txt", "b.txt"]
+            .into_iter()
+            .map(|name| {
+                Operation::planned(
+                    PlanAction::Move,
+                    root.join(name),
+                    root.to_path_buf(),
+                    Some(root.join("docs").join(name)),
+                )
+            })
+            .collect()
+    }
+
+    fn record(journal: &mut Journal, operations: &[Operation], index: usize, status: RecordStatus) {
+        let record = JournalRecord::new(&journal.run_id, index, &operations[index], None, status);
+        journal.append(&record).unwrap();
+    }
+
+    #[test]
+    fn appended_records_read_back_in_order() {
+        let state = tempfile::tempdir().unwrap();
+        let operations = operations(Path::new("/root"));
+        let mut journal = Journal::start(state.path(), RunMode::Run, &operations).unwrap();
+        record(&mut journal, &operations, 0, RecordStatus::Pending);
+        record(&mut journal, &operations, 0, RecordStatus::Committed);
+
+        let records = read_journal(&journal.dir).unwrap();
+        let statuses: Vec<RecordStatus> = records.iter().map(|record| record.status).collect();
+        assert_eq!(statuses, [RecordStatus::Pending, RecordStatus::Committed]);
+        assert_eq!(list_runs(state.path()).unwrap(), [journal.run_id.clone()]);
+        assert!(read_run_info(&journal.dir).unwrap().finished_at.is_none());
+
+        let dir = journal.dir.clone();
+        journal.finish().unwrap();
+        assert!(read_run_info(&dir).unwrap().finished_at.is_some());
+    }
+
+    #[test]
+    fn latest_record_of_each_operation_wins() {
+        let state = tempfile::tempdir().unwrap();
+        let operations = operations(Path::new("/root"));
+        let mut journal = Journal::start(state.path(), RunMode::Run, &operations).unwrap();
+        record(&mut journal, &operations, 1, RecordStatus::Pending);
+        record(&mut journal, &operations, 0, RecordStatus::Pending);
+        record(&mut journal, &operations, 0, RecordStatus::Committed);
+        record(&mut journal, &operations, 1, RecordStatus::Failed);
+
+        let latest = latest_records(read_journal(&journal.dir).unwrap());
+        let summary: Vec<(usize, RecordStatus)> = latest
+            .iter()
+            .map(|record| (record.index, record.status))
+            .collect();
+        assert_eq!(
+            summary,
+            [(0, RecordStatus::Committed), (1, RecordStatus::Failed)]
+        );
+    }
+
+    #[test]
+    fn torn_last_line_is_dropped_and_cut_on_reopen() {
+        let state = tempfile::tempdir().unwrap();
+        let operations = operations(Path::new("/root"));
+        let mut journal = Journal::start(state.path(), RunMode::Run, &operations).unwrap();
+        record(&mut journal, &operations, 0, RecordStatus::Pending);
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .unwrap();
+        file.write_all(b"{\"run_id\":\"torn").unwrap();
+        assert_eq!(read_journal(&journal.dir).unwrap().len(), 1);
+
+        let mut reopened = Journal::reopen(state.path(), &journal.run_id).unwrap();
+        record(&mut reopened, &operations, 0, RecordStatus::Committed);
+        let records = read_journal(&journal.dir).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(read_to_string(journal.path()).unwrap().ends_with("}\n"));
+    }
+
+    #[test]
+    fn broken_line_before_the_end_is_an_error() {
+        let state = tempfile::tempdir().unwrap();
+        let operations = operations(Path::new("/root"));
+        let mut journal = Journal::start(state.path(), RunMode::Run, &operations).unwrap();
+        write(journal.path(), "not json\n").unwrap();
+        record(&mut journal, &operations, 0, RecordStatus::Pending);
+        assert!(read_journal(&journal.dir).is_err());
+    }
+}
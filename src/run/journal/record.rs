@@ -0,0 +1,79 @@
+use crate::run::planner::plan::{Operation, PlanAction};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// One line of journal.jsonl. An operation gets a pending line before it runs
+// and a committed or failed line after, so a pending line without a follow-up
+// marks the operation that was in flight when the process died.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRecord {
+    pub run_id: String,
+    pub timestamp: String,
+    // position of the operation in the plan.json of the run
+    pub index: usize,
+    pub action: PlanAction,
+    pub source: PathBuf,
+    pub destination: Option<PathBuf>,
+    pub size: Option<u64>,
+    // blake3 of the source content, taken before the operation ran
+    pub hash: Option<String>,
+    pub rule: Option<String>,
+    pub status: RecordStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordStatus {
+    Pending,
+    Committed,
+    Failed,
+    // appended to the journal of the original run once an undo reversed it
+    Undone,
+}
+
+impl JournalRecord {
+    pub fn new(
+        run_id: &str,
+        index: usize,
+        operation: &Operation,
+        hash: Option<String>,
+        status: RecordStatus,
+    ) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            timestamp: Local::now().to_rfc3339(),
+            index,
+            action: operation.action,
+            source: operation.source.clone(),
+            destination: operation.destination.clone(),
+            size: operation.source_size,
+            hash,
+            rule: operation.rule.clone(),
+            status,
+            error: None,
+        }
+    }
+}
+
+// run.json, rewritten once more with finished_at when the run ends cleanly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunInfo {
+    pub run_id: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub mode: RunMode,
+    pub roots: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunMode {
+    // `neatd run`, planned and executed in one go
+    Run,
+    // `neatd apply`, executing a saved plan file
+    Apply,
+    // `neatd undo`, moving the files of an earlier run back
+    Undo,
+}
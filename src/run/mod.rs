@@ -1,4 +1,12 @@
 pub mod config;
 pub mod entries;
+pub mod executor;
+pub mod explain;
+pub mod history;
+pub mod journal;
+pub mod planner;
 pub mod policies;
+pub mod quarantine;
+pub mod recover;
 pub mod scanner;
+pub mod undo;
@@ -0,0 +1,256 @@
+use crate::run::{
+    config::config::{Config, ConflictStrategy},
+    entries::fs_entry::SkipReason,
+    planner::plan::{Operation, PlanAction},
+};
+use std::{
+    collections::HashMap,
+    fs::symlink_metadata,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// Walks the plan in order and makes every destination unique. A destination is
+// taken when it exists on disk or an earlier operation already claimed it; the
+// strategy of the rule behind the later operation decides what happens.
+pub fn resolve_conflicts(config: &Config, quarantine: &Path, operations: &mut [Operation]) {
+    resolve_with(|rule| strategy_for(config, rule), quarantine, operations);
+}
+
+fn resolve_with(
+    strategy_for: impl Fn(Option<&str>) -> ConflictStrategy,
+    quarantine: &Path,
+    operations: &mut [Operation],
+) {
+    // destination -> index of the operation that currently writes it
+    let mut claims: HashMap<PathBuf, usize> = HashMap::new();
+
+    for index in 0..operations.len() {
+        let operation = &operations[index];
+        if operation.action == PlanAction::Skip {
+            continue;
+        }
+        let Some(destination) = operation.destination.clone() else {
+            continue;
+        };
+        let claimed = claims.get(&destination).copied();
+        let on_disk = symlink_metadata(&destination).ok();
+        if claimed.is_none() && on_disk.is_none() {
+            claims.insert(destination, index);
+            continue;
+        }
+
+        // the quarantine keeps every deleted file, whatever the rule says
+        let strategy = match operation.action {
+            PlanAction::Delete => ConflictStrategy::Rename,
+            _ => strategy_for(operation.rule.as_deref()),
+        };
+        match strategy {
+            ConflictStrategy::Rename => {
+                let renamed = free_path(&destination, &claims);
+                operations[index].destination = Some(renamed.clone());
+                claims.insert(renamed, index);
+            }
+            ConflictStrategy::Quarantine => {
+                let Some(file_name) = destination.file_name() else {
+                    continue;
+                };
+                let quarantined = free_path(&quarantine.join(file_name), &claims);
+                operations[index].destination = Some(quarantined.clone());
+                operations[index].quarantine = Some(quarantine.to_path_buf());
+                claims.insert(quarantined, index);
+            }
+            ConflictStrategy::Skip => {
+                skip(operations, index, SkipReason::DestinationTaken(destination));
+            }
+            ConflictStrategy::Overwrite => {
+                // two files of one plan never land on the same path, the
+                // later one replaces what is on disk and the earlier one stays
+                if let Some(holder) = claimed {
+                    skip(
+                        operations,
+                        holder,
+                        SkipReason::ConflictLost(destination.clone()),
+                    );
+                }
+                operations[index].overwrite = on_disk.is_some();
+                claims.insert(destination, index);
+            }
+            strategy @ (ConflictStrategy::KeepNewest | ConflictStrategy::KeepOldest) => {
+                // whoever holds the destination has already beaten the file on disk
+                let held_modified = match claimed {
+                    Some(holder) => operations[holder].source_modified,
+                    None => on_disk
+                        .as_ref()
+                        .and_then(|metadata| metadata.modified().ok()),
+                };
+                let incoming_wins = wins(strategy, operation.source_modified, held_modified);
+                if !incoming_wins {
+                    skip(operations, index, SkipReason::ConflictLost(destination));
+                    continue;
+                }
+                if let Some(holder) = claimed {
+                    skip(
+                        operations,
+                        holder,
+                        SkipReason::ConflictLost(destination.clone()),
+                    );
+                }
+                operations[index].overwrite = on_disk.is_some();
+                claims.insert(destination, index);
+            }
+        }
+    }
+}
+
+fn strategy_for(config: &Config, rule: Option<&str>) -> ConflictStrategy {
+    config
+        .rules
+        .iter()
+        .find(|candidate| Some(candidate.name.as_str()) == rule)
+        .and_then(|candidate| candidate.action.as_ref())
+        .map(|action| action.conflict_strategy)
+        .unwrap_or_default()
+}
+
+// unknown times never win, the file already holding the destination stays
+fn wins(
+    strategy: ConflictStrategy,
+    incoming: Option<SystemTime>,
+    held: Option<SystemTime>,
+) -> bool {
+    match (incoming, held) {
+        (Some(incoming), Some(held)) if strategy == ConflictStrategy::KeepNewest => incoming > held,
+        (Some(incoming), Some(held)) => incoming < held,
+        _ => false,
+    }
+}
+
+fn skip(operations: &mut [Operation], index: usize, reason: SkipReason) {
+    let operation = &operations[index];
+    operations[index] = Operation::skipped(
+        operation.source.clone(),
+        operation.root.clone(),
+        operation.rule.clone(),
+        reason,
+    );
+}
+
+// report.pdf -> report-1.pdf, report-2.pdf, ... until one is neither on disk nor claimed
+fn free_path(path: &Path, claims: &HashMap<PathBuf, usize>) -> PathBuf {
+    let is_free =
+        |candidate: &Path| !claims.contains_key(candidate) && symlink_metadata(candidate).is_err();
+    if is_free(path) {
+        return path.to_path_buf();
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    let mut counter: u64 = 1;
+    loop {
+        let candidate = path.with_file_name(format!("{}-{}{}", stem, counter, extension));
+        if is_free(&candidate) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+
+    const QUARANTINE: &str = "/neatd-test/quarantine";
+
+    fn resolve(strategy: ConflictStrategy, operations: &mut [Operation]) {
+        resolve_with(|_| strategy, Path::new(QUARANTINE), operations);
+    }
+
+    fn moved(source: PathBuf, destination: PathBuf) -> Operation {
+        let root = source.parent().unwrap().to_path_buf();
+        let mut operation = Operation::planned(PlanAction::Move, source, root, Some(destination));
+        operation.source_modified = Some(SystemTime::UNIX_EPOCH);
+        operation
+    }
+
+    fn two_sources_one_destination(destination: &Path) -> Vec<Operation> {
+        vec![
+            moved(
+                PathBuf::from("/neatd-test/root/a/report.pdf"),
+                destination.to_path_buf(),
+            ),
+            moved(
+                PathBuf::from("/neatd-test/root/b/report.pdf"),
+                destination.to_path_buf(),
+            ),
+        ]
+    }
+
+    fn writers(operations: &[Operation]) -> Vec<&Operation> {
+        operations
+            .iter()
+            .filter(|operation| operation.action != PlanAction::Skip)
+            .collect()
+    }
+
+    #[test]
+    fn overwrite_never_lets_two_operations_write_one_destination() {
+        let destination = Path::new("/neatd-test/root/documents/report.pdf");
+        let mut operations = two_sources_one_destination(destination);
+        resolve(ConflictStrategy::Overwrite, &mut operations);
+
+        let writers = writers(&operations);
+        assert_eq!(writers.len(), 1);
+        assert_eq!(
+            writers[0].source,
+            Path::new("/neatd-test/root/b/report.pdf")
+        );
+        // nothing on disk to replace
+        assert!(!writers[0].overwrite);
+        assert!(matches!(
+            operations[0].skip_reason,
+            Some(SkipReason::ConflictLost(_))
+        ));
+    }
+
+    #[test]
+    fn overwrite_in_plan_replaces_the_file_on_disk_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let destination = dir.path().join("report.pdf");
+        write(&destination, "on disk").unwrap();
+        let mut operations = two_sources_one_destination(&destination);
+        resolve(ConflictStrategy::Overwrite, &mut operations);
+
+        let writers = writers(&operations);
+        assert_eq!(writers.len(), 1);
+        assert_eq!(
+            writers[0].source,
+            Path::new("/neatd-test/root/b/report.pdf")
+        );
+        assert!(writers[0].overwrite);
+        assert_eq!(
+            operations[0].skip_reason.as_ref().map(ToString::to_string),
+            Some(SkipReason::ConflictLost(destination).to_string())
+        );
+    }
+
+    #[test]
+    fn rename_gives_each_operation_its_own_destination() {
+        let destination = Path::new("/neatd-test/root/documents/report.pdf");
+        let mut operations = two_sources_one_destination(destination);
+        resolve(ConflictStrategy::Rename, &mut operations);
+
+        assert_eq!(operations[0].destination.as_deref(), Some(destination));
+        assert_eq!(
+            operations[1].destination.as_deref(),
+            Some(Path::new("/neatd-test/root/documents/report-1.pdf"))
+        );
+    }
+}
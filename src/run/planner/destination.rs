@@ -0,0 +1,203 @@
+use crate::run::{
+    config::{
+        config::{Action, Config},
+        template::{Segment, Template, Token},
+    },
+    entries::fs_entry::FsEntry,
+    planner::{
+        layout::{entry_date, layout_dir},
+        naming::normalize_name,
+    },
+    policies::config_policy::ConfigPolicy,
+    scanner::process_file::entry_extension,
+};
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::Write,
+    path::{Component, Path, PathBuf},
+};
+
+// Final path of a file handled by `action`: the rendered `to` template below
+// the root (unless absolute), the date layout, then the file name unless the
+// template spells it out itself. The name goes through `[naming]` last, so
+// conflicts are resolved against the name the file will really get.
+pub fn resolve_destination(
+    config: &Config,
+    entry: &FsEntry,
+    rule: &str,
+    action: &Action,
+) -> Option<PathBuf> {
+    let file_name = entry.path.file_name()?;
+    // only a rename may leave out `to`, the file then stays where it is
+    let base = if action.to.as_os_str().is_empty() {
+        entry.path.parent()?
+    } else {
+        &entry.root
+    };
+    let original = file_name.to_string_lossy();
+    let rendered = render(config, entry, rule, &action.template, &original);
+    let mut destination = if action.to.is_absolute() {
+        PathBuf::from(rendered)
+    } else {
+        base.join(below_base(&rendered))
+    };
+
+    let layout = if action.use_layout {
+        layout_dir(&config.layout, entry.metadata.as_ref())
+    } else {
+        None
+    };
+    if action.template.names_file {
+        let name = destination.file_name()?.to_os_string();
+        destination.pop();
+        if let Some(dated) = layout {
+            destination.push(dated);
+        }
+        destination.push(name);
+    } else {
+        if let Some(dated) = layout {
+            destination.push(dated);
+        }
+        destination.push(new_name(config, entry, rule, action, file_name));
+    }
+
+    if config.naming.normalize_names
+        && let Some(name) = destination.file_name()
+    {
+        let normalized = normalize_name(&config.naming, &name.to_string_lossy());
+        destination.set_file_name(normalized);
+    }
+    Some(ConfigPolicy::lexical_normalize(&destination))
+}
+
+// `pattern` is replaced first, `name` then renders {stem}, {ext} and {name}
+// from the result. A name that comes out empty keeps the original one.
+fn new_name(
+    config: &Config,
+    entry: &FsEntry,
+    rule: &str,
+    action: &Action,
+    file_name: &OsStr,
+) -> OsString {
+    if !action.renames() {
+        return file_name.to_os_string();
+    }
+    let mut name = file_name.to_string_lossy().into_owned();
+    if let Some(regex) = action.name_regex.as_ref() {
+        name = regex
+            .replace(&name, action.replacement.as_str())
+            .into_owned();
+    }
+    if let Some(template) = action.name_template.as_ref() {
+        name = render(config, entry, rule, template, &name);
+    }
+    // {parent} or a replacement may hold a separator, the name must stay one component
+    let name = name.replace(['/', '\\'], "_");
+    if name.is_empty() || name == "." || name == ".." {
+        return file_name.to_os_string();
+    }
+    OsString::from(name)
+}
+
+// {stem}, {ext} and {name} come from `name`, everything else from the entry
+fn render(config: &Config, entry: &FsEntry, rule: &str, template: &Template, name: &str) -> String {
+    let name = Path::new(name);
+    let extension = entry_extension(name).unwrap_or_default();
+    let mut rendered = String::new();
+
+    for segment in &template.segments {
+        let token = match segment {
+            Segment::Literal(text) => {
+                rendered.push_str(text);
+                continue;
+            }
+            Segment::Token(token) => token,
+        };
+        let value: String = match token {
+            Token::Rule => component(rule),
+            Token::Ext => extension.clone(),
+            Token::Stem => lossy(name.file_stem()),
+            Token::Name => lossy(name.file_name()),
+            Token::Parent => entry
+                .path
+                .parent()
+                .and_then(|parent| parent.strip_prefix(&entry.root).ok())
+                .map(|parent| parent.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            Token::Root => lossy(entry.root.file_name()),
+            Token::Year => date(config, entry, "%Y"),
+            Token::Month => date(config, entry, "%m"),
+            Token::Day => date(config, entry, "%d"),
+            Token::Date(format) => date(config, entry, format),
+            Token::SizeBucket => size_bucket(entry).to_string(),
+        };
+        rendered.push_str(&value);
+    }
+
+    // "{stem}.{ext}" for a file without extension would end in a dot
+    if template.names_file {
+        let trimmed = rendered.trim_end_matches('.').len();
+        rendered.truncate(trimmed);
+    }
+    rendered
+}
+
+// A relative template stays relative once rendered: an empty {parent} in
+// "{parent}/{stem}.{ext}" leaves a leading "/" that would otherwise replace
+// the root. Empty components and "." vanish, ".." is left to the roots check.
+fn below_base(rendered: &str) -> PathBuf {
+    Path::new(rendered)
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_) | Component::ParentDir))
+        .collect()
+}
+
+fn lossy(value: Option<&OsStr>) -> String {
+    value
+        .map(|value| value.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// a rule name is free text, it must not open new directories
+fn component(value: &str) -> String {
+    value.replace(['/', '\\'], "-")
+}
+
+fn date(config: &Config, entry: &FsEntry, format: &str) -> String {
+    let date = entry_date(&config.layout, entry.metadata.as_ref());
+    let mut formatted = String::new();
+    // formats are checked at load, this only guards against a panic
+    let _ = write!(formatted, "{}", date.format(format));
+    formatted
+}
+
+fn size_bucket(entry: &FsEntry) -> &'static str {
+    const MB: u64 = 1_000_000;
+    match entry
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.size_bytes)
+    {
+        None => "unknown-size",
+        Some(size) if size < MB => "under-1MB",
+        Some(size) if size < 10 * MB => "1-10MB",
+        Some(size) if size < 100 * MB => "10-100MB",
+        Some(size) if size < 1000 * MB => "100MB-1GB",
+        Some(_) => "over-1GB",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rendered_template_stays_below_the_base() {
+        assert_eq!(
+            below_base("/a-2026-10-18.pdf"),
+            Path::new("a-2026-10-18.pdf")
+        );
+        assert_eq!(below_base("sub//./a.pdf"), Path::new("sub/a.pdf"));
+        assert_eq!(below_base("../../etc"), Path::new("../../etc"));
+    }
+}
@@ -0,0 +1,95 @@
+use super::plan::{Plan, PlanAction};
+use crate::run::{entries::fs_entry::SkipReason, scanner::display::display_error};
+use colored::*;
+use std::path::Path;
+
+fn action_val(a: &PlanAction) -> ColoredString {
+    // pad first, then color (better alignment)
+    let padded = format!("{:<7}", a.as_str());
+    match a {
+        PlanAction::Move => padded.bright_blue().bold(),
+        PlanAction::Copy => padded.cyan().bold(),
+        PlanAction::Delete => padded.red().bold(),
+        PlanAction::Skip => padded.bright_black(),
+    }
+}
+
+fn relative<'a>(path: &'a Path, root: &Path) -> &'a Path {
+    path.strip_prefix(root).unwrap_or(path)
+}
+
+pub fn display_plan(plan: &Plan) {
+    println!("\n{}", "PLAN".bright_yellow().bold());
+    println!("{}", "════".bright_black());
+
+    let mut current_root = None;
+    for operation in &plan.operations {
+        if current_root != Some(&operation.root) {
+            println!(
+                "\n{}",
+                operation.root.to_string_lossy().bright_blue().bold()
+            );
+            current_root = Some(&operation.root);
+        }
+        let source = relative(&operation.source, &operation.root).to_string_lossy();
+
+        match (&operation.destination, &operation.skip_reason) {
+            (Some(destination), _) => {
+                let rule = operation.rule.as_deref().unwrap_or_default();
+                let overwrite = if operation.overwrite {
+                    " (overwrite)".red()
+                } else {
+                    "".normal()
+                };
+                println!(
+                    "  {} {} {} {}{} {}",
+                    action_val(&operation.action),
+                    source.white(),
+                    "→".bright_black(),
+                    relative(destination, &operation.root)
+                        .to_string_lossy()
+                        .green(),
+                    overwrite,
+                    format!("[{}]", rule).bright_black()
+                );
+            }
+            (None, reason) => {
+                let reason = reason.as_ref().map(|r| r.to_string()).unwrap_or_default();
+                println!(
+                    "  {} {} {}",
+                    action_val(&operation.action),
+                    source.white(),
+                    reason.bright_black()
+                );
+            }
+        }
+    }
+
+    if !plan.errors.is_empty() {
+        println!("\n{}", "ERRORS".red().bold());
+        for error in &plan.errors {
+            display_error(error);
+        }
+    }
+
+    let count = |action: PlanAction| {
+        plan.operations
+            .iter()
+            .filter(|operation| operation.action == action)
+            .count()
+    };
+    let pending = plan
+        .operations
+        .iter()
+        .filter(|operation| matches!(operation.skip_reason, Some(SkipReason::Settling { .. })))
+        .count();
+    println!(
+        "\n{} move, {} copy, {} delete, {} skip, {} pending, {} errors",
+        count(PlanAction::Move).to_string().bright_blue().bold(),
+        count(PlanAction::Copy).to_string().cyan().bold(),
+        count(PlanAction::Delete).to_string().red().bold(),
+        count(PlanAction::Skip).to_string().bright_black(),
+        pending.to_string().yellow().bold(),
+        plan.errors.len().to_string().red().bold()
+    );
+}
@@ -0,0 +1,34 @@
+use crate::run::{
+    config::config::{DateSource, Layout},
+    entries::fs_entry::EntryMetaData,
+};
+use chrono::{DateTime, Local};
+use std::{fmt::Write, path::PathBuf, time::SystemTime};
+
+// Date sub-directory for `use_layout` destinations, e.g. "2025/12" for "%Y/%m".
+pub fn layout_dir(layout: &Layout, metadata: Option<&EntryMetaData>) -> Option<PathBuf> {
+    let date = entry_date(layout, metadata);
+    let mut formatted = String::new();
+    // date_format is checked at load, this only guards against a panic
+    write!(formatted, "{}", date.format(&layout.date_format)).ok()?;
+    Some(PathBuf::from(formatted))
+}
+
+// `now` ends every fallback chain, so there is always a date
+pub fn entry_date(layout: &Layout, metadata: Option<&EntryMetaData>) -> DateTime<Local> {
+    layout
+        .date_source
+        .fallback_chain()
+        .find_map(|source| timestamp(source, metadata))
+        .unwrap_or_else(SystemTime::now)
+        .into()
+}
+
+fn timestamp(source: DateSource, metadata: Option<&EntryMetaData>) -> Option<SystemTime> {
+    match source {
+        DateSource::Modified => metadata?.modified,
+        DateSource::Created => metadata?.created,
+        DateSource::Accessed => metadata?.accessed,
+        DateSource::Now => Some(SystemTime::now()),
+    }
+}
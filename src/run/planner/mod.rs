@@ -0,0 +1,135 @@
+pub mod conflicts;
+pub mod destination;
+pub mod display;
+pub mod layout;
+pub mod naming;
+pub mod placed;
+pub mod plan;
+pub mod plan_file;
+pub mod protected;
+pub mod roots;
+pub mod settle;
+
+use crate::run::{
+    config::config::{ActionType, Config, resolve_path},
+    entries::fs_entry::{EntryError, FsEntry, SkipReason},
+    planner::{
+        conflicts::resolve_conflicts,
+        destination::resolve_destination,
+        placed::skip_placed,
+        plan::{Operation, Plan, PlanAction},
+        protected::enforce_protected,
+        roots::enforce_roots,
+        settle::defer_unsettled,
+    },
+    scanner::Scan,
+};
+use std::path::Path;
+
+pub fn build_plan(config: &Config, base_dir: &Path, scan: Scan) -> Plan {
+    let mut plan = Plan {
+        operations: Vec::new(),
+        errors: scan.errors,
+    };
+
+    let quarantine = resolve_path(base_dir, &config.paths.quarantine);
+    for mut entry in scan.entries {
+        plan.operations
+            .push(build_operation(config, &quarantine, &entry));
+        plan.errors.append(&mut entry.errors);
+    }
+
+    // roots may overlap in config order, the source path is the stable key
+    plan.operations
+        .sort_by(|left, right| left.source.cmp(&right.source));
+
+    // an operation rejected here never claims a destination
+    plan.errors.append(&mut check_operations(
+        config,
+        base_dir,
+        &mut plan.operations,
+    ));
+    // resolved after sorting, so the same file always wins a collision
+    resolve_conflicts(config, &quarantine, &mut plan.operations);
+    // a renamed destination, or one moved to the quarantine, is checked again
+    enforce_roots(config, base_dir, &mut plan.operations);
+    enforce_protected(config, &mut plan.operations);
+    plan
+}
+
+// Every check that turns a single operation into a skip, in planning order.
+// explain runs the same checks, so both reach the same decision.
+pub fn check_operations(
+    config: &Config,
+    base_dir: &Path,
+    operations: &mut [Operation],
+) -> Vec<EntryError> {
+    let state_dir = resolve_path(base_dir, &config.paths.state_dir);
+    let errors = skip_placed(&state_dir, operations);
+    enforce_roots(config, base_dir, operations);
+    enforce_protected(config, operations);
+    defer_unsettled(config, &state_dir, operations);
+    errors
+}
+
+pub fn build_operation(config: &Config, quarantine: &Path, entry: &FsEntry) -> Operation {
+    let source = entry.path.clone();
+    let root = entry.root.clone();
+
+    if let Some(reason) = entry.skip.clone() {
+        return Operation::skipped(source, root, None, reason);
+    }
+    let Some(matched) = entry.matched.as_ref() else {
+        return Operation::skipped(source, root, None, SkipReason::NoRuleMatched);
+    };
+    let rule = Some(matched.name.clone());
+    let Some(action) = matched.action.as_ref() else {
+        let reason = SkipReason::NoAction(matched.name.clone());
+        return Operation::skipped(source, root, rule, reason);
+    };
+
+    if action.r#type == ActionType::Delete {
+        return delete_operation(quarantine, entry, rule);
+    }
+    let Some(destination) = resolve_destination(config, entry, &matched.name, action) else {
+        return Operation::skipped(source, root, rule, SkipReason::NoFileName);
+    };
+    if destination == source {
+        return Operation::skipped(source, root, rule, SkipReason::AlreadyInPlace);
+    }
+
+    Operation {
+        source,
+        root,
+        destination: Some(destination),
+        action: PlanAction::from(action.r#type),
+        rule,
+        skip_reason: None,
+        source_size: entry.metadata.as_ref().and_then(|m| m.size_bytes),
+        source_modified: entry.metadata.as_ref().and_then(|m| m.modified),
+        overwrite: false,
+        quarantine: None,
+    }
+}
+
+// nothing is removed while planning or executing: the file goes to the
+// quarantine and only a purge deletes it for good
+fn delete_operation(quarantine: &Path, entry: &FsEntry, rule: Option<String>) -> Operation {
+    let source = entry.path.clone();
+    let root = entry.root.clone();
+    let Some(file_name) = source.file_name() else {
+        return Operation::skipped(source, root, rule, SkipReason::NoFileName);
+    };
+    Operation {
+        destination: Some(quarantine.join(file_name)),
+        action: PlanAction::Delete,
+        rule,
+        skip_reason: None,
+        source_size: entry.metadata.as_ref().and_then(|m| m.size_bytes),
+        source_modified: entry.metadata.as_ref().and_then(|m| m.modified),
+        overwrite: false,
+        quarantine: Some(quarantine.to_path_buf()),
+        source,
+        root,
+    }
+}
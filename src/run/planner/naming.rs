@@ -0,0 +1,128 @@
+use crate::run::config::config::{NameCase, Naming};
+use deunicode::deunicode_with_tofu;
+use unicode_normalization::UnicodeNormalization;
+
+// characters no file name may hold on at least one common filesystem
+const RESERVED: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+// The `[naming]` pipeline: NFC, transliterate or clean up characters, collapse
+// whitespace, drop a " (1)" copy suffix, apply the case, cap the length.
+// Returns the name unchanged when nothing usable would be left of it.
+pub fn normalize_name(naming: &Naming, name: &str) -> String {
+    let mut normalized: String = name.nfc().collect();
+    if naming.transliterate {
+        normalized = deunicode_with_tofu(&normalized, "");
+    }
+    let normalized = clean(&normalized);
+
+    let (stem, extension) = split_extension(&normalized);
+    let mut stem = stem.trim().to_string();
+    let mut extension = extension.map(|extension| extension.trim().to_string());
+    if !naming.keep_copy_suffix {
+        stem = strip_copy_suffix(&stem).to_string();
+    }
+    match naming.case {
+        NameCase::Preserve => {}
+        NameCase::Lower => {
+            stem = stem.to_lowercase();
+            extension = extension.map(|extension| extension.to_lowercase());
+        }
+        NameCase::Slug => {
+            stem = slug(&stem);
+            extension = extension.map(|extension| slug(&extension));
+        }
+    }
+    let extension = extension.filter(|extension| !extension.is_empty());
+
+    // the extension is kept whole, only the stem gets shorter
+    let budget = naming.max_length().saturating_sub(
+        extension
+            .as_ref()
+            .map_or(0, |extension| extension.len() + 1),
+    );
+    truncate(&mut stem, budget);
+    if stem.is_empty() {
+        return name.to_string();
+    }
+    match extension {
+        Some(extension) => format!("{}.{}", stem, extension),
+        None => stem,
+    }
+}
+
+// drops control characters, replaces reserved ones with "_" and collapses
+// every run of whitespace into a single space
+fn clean(name: &str) -> String {
+    let mut cleaned = String::with_capacity(name.len());
+    let mut in_space = false;
+    for c in name.chars() {
+        if c.is_whitespace() {
+            in_space = true;
+            continue;
+        }
+        if c.is_control() {
+            continue;
+        }
+        if in_space && !cleaned.is_empty() {
+            cleaned.push(' ');
+        }
+        in_space = false;
+        cleaned.push(if RESERVED.contains(&c) { '_' } else { c });
+    }
+    cleaned
+}
+
+// "archive.tar.gz" -> ("archive.tar", Some("gz")), ".bashrc" -> (".bashrc", None)
+fn split_extension(name: &str) -> (&str, Option<&str>) {
+    match name.rfind('.') {
+        Some(dot) if dot > 0 && dot + 1 < name.len() => (&name[..dot], Some(&name[dot + 1..])),
+        _ => (name, None),
+    }
+}
+
+// "report (1)" -> "report", a stem that is only "(1)" stays as it is
+fn strip_copy_suffix(stem: &str) -> &str {
+    let Some(inner) = stem.strip_suffix(')') else {
+        return stem;
+    };
+    let Some(open) = inner.rfind('(') else {
+        return stem;
+    };
+    let digits = &inner[open + 1..];
+    let base = inner[..open].trim_end();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) || base.is_empty() {
+        return stem;
+    }
+    base
+}
+
+fn slug(value: &str) -> String {
+    let mut slug = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let trimmed = slug.trim_end_matches('-').len();
+    slug.truncate(trimmed);
+    slug
+}
+
+// cuts to at most `max` bytes without splitting a character
+fn truncate(value: &mut String, max: usize) {
+    if value.len() <= max {
+        return;
+    }
+    let mut cut = max;
+    while !value.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    value.truncate(cut);
+    // a cut inside "a - b" must not leave a dangling separator
+    let trimmed = value
+        .trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '_' | '.'))
+        .len();
+    value.truncate(trimmed);
+}
@@ -0,0 +1,267 @@
+use crate::run::{
+    entries::fs_entry::{EntryError, EntryOp, Severity, SkipReason},
+    journal::{
+        latest_records, list_runs, read_journal, read_run_info,
+        record::{RecordStatus, RunMode},
+        runs_dir,
+    },
+    planner::plan::{Operation, PlanAction},
+    scanner::set_error::parse_errors,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, read_to_string, rename, write},
+    io,
+    path::{Path, PathBuf},
+};
+
+// state_dir/placed.json: the placements of every finished run, so a plan only
+// reads the journals of runs that finished since
+const PLACED_FILE: &str = "placed.json";
+
+// a file an earlier run moved or copied to where it is now
+#[derive(Debug, Serialize, Deserialize)]
+struct Placement {
+    run_id: String,
+    size: Option<u64>,
+    // only this rule leaves the file alone, any other may still act on it
+    rule: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PlacedIndex {
+    // the newest run folded in, runs after it are read from their journals
+    through: Option<String>,
+    placements: HashMap<PathBuf, Placement>,
+}
+
+// Destinations of the committed moves and copies of earlier runs that are
+// still where they were put, the latest run wins. A run that cannot be read
+// is reported and left out, its files may then be planned once more.
+fn load_placements(state_dir: &Path) -> (HashMap<PathBuf, Placement>, Vec<EntryError>) {
+    let (index, errors) = fold_runs(state_dir);
+    (index.placements, errors)
+}
+
+// the run that put this very file here under `rule`, a file of another size
+// that took its path since is not the one neatd placed
+fn placed_by<'a>(
+    placements: &'a HashMap<PathBuf, Placement>,
+    path: &Path,
+    size: Option<u64>,
+    rule: Option<&str>,
+) -> Option<&'a str> {
+    placements
+        .get(path)
+        .filter(|placement| placement.size == size && placement.rule.as_deref() == rule)
+        .map(|placement| placement.run_id.as_str())
+}
+
+// A template that starts with a token or a rename in place writes into the
+// scanned tree. Skipping what a rule already placed keeps the next run from
+// renaming its own output again ("b-2026-10-18-2026-10-18.pdf").
+pub fn skip_placed(state_dir: &Path, operations: &mut [Operation]) -> Vec<EntryError> {
+    let (placements, errors) = load_placements(state_dir);
+    if placements.is_empty() {
+        return errors;
+    }
+    for operation in operations.iter_mut() {
+        if operation.action == PlanAction::Skip {
+            continue;
+        }
+        let Some(run_id) = placed_by(
+            &placements,
+            &operation.source,
+            operation.source_size,
+            operation.rule.as_deref(),
+        ) else {
+            continue;
+        };
+        *operation = Operation::skipped(
+            operation.source.clone(),
+            operation.root.clone(),
+            operation.rule.clone(),
+            SkipReason::AlreadyPlaced(run_id.to_string()),
+        );
+    }
+    errors
+}
+
+// Folds the runs finished since into placed.json. Called whenever a run ends,
+// best effort: a stale index only means more journals to read next time.
+pub fn record_placements(state_dir: &Path) -> Option<EntryError> {
+    let path = state_dir.join(PLACED_FILE);
+    let (index, _) = fold_runs(state_dir);
+    save_index(&path, &index)
+        .err()
+        .map(|error| parse_errors(&path, EntryOp::Journal, Some(error), Severity::Warning))
+}
+
+fn fold_runs(state_dir: &Path) -> (PlacedIndex, Vec<EntryError>) {
+    let mut index = load_index(&state_dir.join(PLACED_FILE));
+    let mut errors: Vec<EntryError> = Vec::new();
+    let warn = |path: &Path, error: String| {
+        parse_errors(
+            path,
+            EntryOp::Journal,
+            Some(io::Error::other(error)),
+            Severity::Warning,
+        )
+    };
+
+    let run_ids = match list_runs(state_dir) {
+        Ok(run_ids) => run_ids,
+        Err(error) => return (index, vec![warn(&runs_dir(state_dir), error)]),
+    };
+    // the cursor only moves past runs that are finished and readable, anything
+    // else is read again next time
+    let mut advance = true;
+    for run_id in run_ids {
+        if index
+            .through
+            .as_ref()
+            .is_some_and(|through| run_id <= *through)
+        {
+            continue;
+        }
+        let run_dir = runs_dir(state_dir).join(&run_id);
+        let run = read_run_info(&run_dir)
+            .and_then(|info| read_journal(&run_dir).map(|records| (info, records)));
+        let (info, records) = match run {
+            Ok(run) => run,
+            Err(error) => {
+                errors.push(warn(&run_dir, error));
+                advance = false;
+                continue;
+            }
+        };
+        for record in latest_records(records) {
+            if record.status != RecordStatus::Committed {
+                continue;
+            }
+            // whatever left a path, moved on or put back by an undo, is no
+            // longer placed there
+            if matches!(record.action, PlanAction::Move | PlanAction::Delete) {
+                index.placements.remove(&record.source);
+            }
+            if info.mode != RunMode::Undo
+                && matches!(record.action, PlanAction::Move | PlanAction::Copy)
+                && let Some(destination) = record.destination
+            {
+                index.placements.insert(
+                    destination,
+                    Placement {
+                        run_id: run_id.clone(),
+                        size: record.size,
+                        rule: record.rule,
+                    },
+                );
+            }
+        }
+        advance &= info.finished_at.is_some();
+        if advance {
+            index.through = Some(run_id);
+        }
+    }
+    (index, errors)
+}
+
+// a missing or unreadable index only means every journal is read again
+fn load_index(path: &Path) -> PlacedIndex {
+    read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(path: &Path, index: &PlacedIndex) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(index).map_err(io::Error::other)?;
+    let temp = path.with_extension("json.tmp");
+    write(&temp, json + "\n")?;
+    rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::journal::{JOURNAL_FILE, Journal, record::JournalRecord};
+    use std::fs::remove_file;
+
+    fn placed(state_dir: &Path, operation: &Operation) -> String {
+        let mut journal =
+            Journal::start(state_dir, RunMode::Run, std::slice::from_ref(operation)).unwrap();
+        let record =
+            JournalRecord::new(&journal.run_id, 0, operation, None, RecordStatus::Committed);
+        journal.append(&record).unwrap();
+        let run_id = journal.run_id.clone();
+        journal.finish().unwrap();
+        run_id
+    }
+
+    fn ruled(rule: &str, source: PathBuf, destination: Option<PathBuf>) -> Operation {
+        let mut operation = Operation::planned(
+            PlanAction::Move,
+            source,
+            PathBuf::from("/root"),
+            destination,
+        );
+        operation.rule = Some(rule.to_string());
+        operation
+    }
+
+    #[test]
+    fn only_the_placing_rule_leaves_the_file_alone() {
+        let state = tempfile::tempdir().unwrap();
+        let placed_at = PathBuf::from("/root/b-2026-10-18.pdf");
+        placed(
+            state.path(),
+            &ruled(
+                "Dated",
+                PathBuf::from("/root/b.pdf"),
+                Some(placed_at.clone()),
+            ),
+        );
+
+        let mut operations = [
+            ruled(
+                "Dated",
+                placed_at.clone(),
+                Some(PathBuf::from("/root/x.pdf")),
+            ),
+            ruled("Retention", placed_at.clone(), None),
+        ];
+        skip_placed(state.path(), &mut operations);
+        assert!(matches!(
+            operations[0].skip_reason,
+            Some(SkipReason::AlreadyPlaced(_))
+        ));
+        assert_eq!(operations[1].action, PlanAction::Move);
+    }
+
+    #[test]
+    fn finished_runs_are_read_from_the_index() {
+        let state = tempfile::tempdir().unwrap();
+        let placed_at = PathBuf::from("/root/docs/a.pdf");
+        let run_id = placed(
+            state.path(),
+            &ruled(
+                "Docs",
+                PathBuf::from("/root/a.pdf"),
+                Some(placed_at.clone()),
+            ),
+        );
+        // the journal is no longer needed once the run is in placed.json
+        remove_file(runs_dir(state.path()).join(&run_id).join(JOURNAL_FILE)).unwrap();
+
+        let (placements, errors) = load_placements(state.path());
+        assert!(errors.is_empty());
+        assert_eq!(
+            placed_by(&placements, &placed_at, None, Some("Docs")),
+            Some(run_id.as_str())
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::run::{
+    config::config::ActionType,
+    entries::fs_entry::{EntryError, SkipReason},
+};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::SystemTime};
+
+#[derive(Debug)]
+pub struct Plan {
+    // sorted by source path, so the same tree always yields the same plan
+    pub operations: Vec<Operation>,
+    // scan and metadata errors collected while building the plan
+    pub errors: Vec<EntryError>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Operation {
+    pub source: PathBuf,
+    pub root: PathBuf,
+    // full destination path including the file name, None for skips
+    pub destination: Option<PathBuf>,
+    pub action: PlanAction,
+    pub rule: Option<String>,
+    pub skip_reason: Option<SkipReason>,
+    // source state when the plan was built, checked again before applying
+    pub source_size: Option<u64>,
+    pub source_modified: Option<SystemTime>,
+    // set by conflict resolution when replacing an existing file is intended
+    #[serde(default)]
+    pub overwrite: bool,
+    // quarantine directory when the destination lies in it, the executor
+    // records the file in its index
+    #[serde(default)]
+    pub quarantine: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanAction {
+    Move,
+    Copy,
+    Delete,
+    Skip,
+}
+
+impl From<ActionType> for PlanAction {
+    fn from(action_type: ActionType) -> Self {
+        match action_type {
+            // a rename is a move whose destination differs only by name
+            ActionType::Move | ActionType::Rename => PlanAction::Move,
+            ActionType::Copy => PlanAction::Copy,
+            // a delete only moves the file into quarantine, purge removes it
+            ActionType::Delete => PlanAction::Delete,
+        }
+    }
+}
+
+impl PlanAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PlanAction::Move => "move",
+            PlanAction::Copy => "copy",
+            PlanAction::Delete => "delete",
+            PlanAction::Skip => "skip",
+        }
+    }
+}
+
+impl Operation {
+    pub fn skipped(
+        source: PathBuf,
+        root: PathBuf,
+        rule: Option<String>,
+        reason: SkipReason,
+    ) -> Self {
+        Self {
+            source,
+            root,
+            destination: None,
+            action: PlanAction::Skip,
+            rule,
+            skip_reason: Some(reason),
+            source_size: None,
+            source_modified: None,
+            overwrite: false,
+            quarantine: None,
+        }
+    }
+
+    // an operation as the planner would emit it, for tests that start past planning
+    #[cfg(test)]
+    pub fn planned(
+        action: PlanAction,
+        source: PathBuf,
+        root: PathBuf,
+        destination: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            source,
+            root,
+            destination,
+            action,
+            rule: None,
+            skip_reason: None,
+            source_size: None,
+            source_modified: None,
+            overwrite: false,
+            quarantine: None,
+        }
+    }
+}
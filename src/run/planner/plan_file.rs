@@ -0,0 +1,46 @@
+use super::plan::Operation;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read_to_string, write},
+    path::Path,
+};
+
+// bump when the on-disk layout of a plan changes
+pub const PLAN_FORMAT_VERSION: u32 = 1;
+
+// No timestamps on purpose: the same tree must serialize to the same bytes,
+// so two plan files can be diffed during review.
+#[derive(Serialize, Deserialize)]
+pub struct PlanFile {
+    pub version: u32,
+    pub operations: Vec<Operation>,
+}
+
+// same layout as PlanFile, borrowed so a plan can be saved and still executed
+#[derive(Serialize)]
+struct PlanFileRef<'a> {
+    version: u32,
+    operations: &'a [Operation],
+}
+
+pub fn save_plan(operations: &[Operation], path: &Path) -> Result<(), String> {
+    let plan_file = PlanFileRef {
+        version: PLAN_FORMAT_VERSION,
+        operations,
+    };
+    let json = serde_json::to_string_pretty(&plan_file).map_err(|e| e.to_string())?;
+    write(path, json + "\n").map_err(|e| format!("cannot write {:?}: {}", path, e))
+}
+
+pub fn load_plan(path: &Path) -> Result<PlanFile, String> {
+    let content = read_to_string(path).map_err(|e| format!("cannot read {:?}: {}", path, e))?;
+    let plan_file: PlanFile = serde_json::from_str(&content)
+        .map_err(|e| format!("{:?} is not a valid plan file: {}", path, e))?;
+    if plan_file.version != PLAN_FORMAT_VERSION {
+        return Err(format!(
+            "{:?} uses plan format version {}, this neatd reads version {}",
+            path, plan_file.version, PLAN_FORMAT_VERSION
+        ));
+    }
+    Ok(plan_file)
+}
@@ -0,0 +1,47 @@
+use crate::run::{
+    config::config::Config,
+    entries::fs_entry::SkipReason,
+    planner::plan::{Operation, PlanAction},
+};
+use std::path::Path;
+
+// `safety.protected` wins over every rule: an operation whose source or
+// destination matches one of its globs is skipped. Also run on saved plans,
+// the config may have gained a pattern since.
+pub fn enforce_protected(config: &Config, operations: &mut [Operation]) {
+    if config.safety.protected.is_empty() {
+        return;
+    }
+    for operation in operations.iter_mut() {
+        if operation.action == PlanAction::Skip {
+            continue;
+        }
+        let matched = protected_by(config, &operation.source, &operation.root).or_else(|| {
+            operation
+                .destination
+                .as_deref()
+                .and_then(|destination| protected_by(config, destination, &operation.root))
+        });
+        if let Some(pattern) = matched {
+            *operation = Operation::skipped(
+                operation.source.clone(),
+                operation.root.clone(),
+                operation.rule.clone(),
+                SkipReason::Protected(pattern.to_string()),
+            );
+        }
+    }
+}
+
+pub fn protected_by<'a>(config: &'a Config, path: &Path, root: &Path) -> Option<&'a str> {
+    let globs = &config.safety.protected_globs;
+    let mut matches = globs.matches(path);
+    if matches.is_empty()
+        && let Ok(relative) = path.strip_prefix(root)
+    {
+        matches = globs.matches(relative);
+    }
+    matches
+        .first()
+        .map(|&index| config.safety.protected[index].as_str())
+}
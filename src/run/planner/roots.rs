@@ -0,0 +1,77 @@
+use crate::run::{
+    config::config::{Config, resolve_path},
+    entries::fs_entry::SkipReason,
+    planner::plan::{Operation, PlanAction},
+    policies::config_policy::ConfigPolicy,
+};
+use std::{
+    fs::canonicalize,
+    path::{Path, PathBuf},
+};
+
+// With require_within_roots every destination must stay inside one of the
+// roots once templates, `..` and symlinks are resolved. The configured
+// quarantine is the one place outside them a file may go. Also run on saved
+// and resumed plans, which may have been edited by hand.
+pub fn enforce_roots(config: &Config, base_dir: &Path, operations: &mut [Operation]) {
+    if !config.safety.require_within_roots {
+        return;
+    }
+    let roots = resolved_roots(config, base_dir);
+    let quarantine = [resolve_symlinks(&resolve_path(
+        base_dir,
+        &config.paths.quarantine,
+    ))];
+    for operation in operations.iter_mut() {
+        if operation.action == PlanAction::Skip {
+            continue;
+        }
+        let Some(destination) = operation.destination.as_deref() else {
+            continue;
+        };
+        if operation.quarantine.is_some() && within_roots(&quarantine, destination) {
+            continue;
+        }
+        if !within_roots(&roots, destination) {
+            let reason = SkipReason::OutsideRoots(destination.to_path_buf());
+            *operation = Operation::skipped(
+                operation.source.clone(),
+                operation.root.clone(),
+                operation.rule.clone(),
+                reason,
+            );
+        }
+    }
+}
+
+pub fn resolved_roots(config: &Config, base_dir: &Path) -> Vec<PathBuf> {
+    config
+        .paths
+        .roots
+        .iter()
+        .map(|root| resolve_symlinks(&resolve_path(base_dir, root)))
+        .collect()
+}
+
+// The file name itself is left alone: replacing a symlink replaces the link,
+// not what it points to. Its directory is what has to stay inside.
+pub fn within_roots(roots: &[PathBuf], destination: &Path) -> bool {
+    let destination = ConfigPolicy::lexical_normalize(destination);
+    let (Some(parent), Some(file_name)) = (destination.parent(), destination.file_name()) else {
+        return false;
+    };
+    let resolved = resolve_symlinks(parent).join(file_name);
+    roots.iter().any(|root| resolved.starts_with(root))
+}
+
+// canonicalizes the longest existing ancestor, the directories a move would
+// still create are appended as they are
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = canonicalize(ancestor) {
+            let rest = path.strip_prefix(ancestor).unwrap_or(Path::new(""));
+            return canonical.join(rest);
+        }
+    }
+    path.to_path_buf()
+}
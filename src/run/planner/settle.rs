@@ -0,0 +1,114 @@
+use crate::run::{
+    config::config::Config,
+    entries::fs_entry::{EntryError, EntryOp, Severity, SkipReason},
+    planner::plan::{Operation, PlanAction},
+    scanner::set_error::parse_errors,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, read_to_string, rename, write},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+// state_dir/settle.json: what every unsettled file looked like when last seen
+pub const SETTLE_FILE: &str = "settle.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Observation {
+    size: Option<u64>,
+    modified: Option<SystemTime>,
+    // size and mtime have not changed since
+    since: SystemTime,
+}
+
+// Files whose size or mtime changed within their settle window are turned
+// into pending skips, a later run picks them up. A file seen for the first
+// time counts as stable since its mtime, one that changed since the last run
+// since its new mtime. Nothing is written here: two plans of an unchanged
+// tree are the same, see `record_unsettled`.
+pub fn defer_unsettled(config: &Config, state_dir: &Path, operations: &mut [Operation]) {
+    let previous = load_observations(&state_dir.join(SETTLE_FILE));
+    let now = SystemTime::now();
+
+    for operation in operations.iter_mut() {
+        if operation.action == PlanAction::Skip {
+            continue;
+        }
+        let Some(window) = config.settle_for(operation.rule.as_deref()) else {
+            continue;
+        };
+        let modified = operation.source_modified.map(|modified| modified.min(now));
+        let since = match previous.get(&operation.source) {
+            Some(seen)
+                if seen.size == operation.source_size
+                    && seen.modified == operation.source_modified =>
+            {
+                seen.since
+            }
+            // a size that moved under an unchanged mtime starts over now
+            Some(seen) if seen.modified == operation.source_modified => now,
+            _ => modified.unwrap_or(now),
+        };
+        if now.duration_since(since).unwrap_or(Duration::ZERO) >= window {
+            continue;
+        }
+
+        let (size, modified) = (operation.source_size, operation.source_modified);
+        *operation = Operation::skipped(
+            operation.source.clone(),
+            operation.root.clone(),
+            operation.rule.clone(),
+            SkipReason::Settling { since, window },
+        );
+        // kept for record_unsettled
+        operation.source_size = size;
+        operation.source_modified = modified;
+    }
+}
+
+// Remembers what every pending file looked like, so a change between two runs
+// is seen even when the mtime stays. Only called when the plan is executed,
+// a dry run leaves state_dir alone. Files no longer pending are dropped.
+pub fn record_unsettled(state_dir: &Path, operations: &[Operation]) -> Option<EntryError> {
+    let path = state_dir.join(SETTLE_FILE);
+    let mut waiting: HashMap<PathBuf, Observation> = HashMap::new();
+    for operation in operations {
+        if let Some(SkipReason::Settling { since, .. }) = operation.skip_reason {
+            waiting.insert(
+                operation.source.clone(),
+                Observation {
+                    size: operation.source_size,
+                    modified: operation.source_modified,
+                    since,
+                },
+            );
+        }
+    }
+    if waiting.is_empty() && !path.exists() {
+        return None;
+    }
+    save_observations(&path, &waiting)
+        .err()
+        .map(|error| parse_errors(&path, EntryOp::Journal, Some(error), Severity::Warning))
+}
+
+// a missing or unreadable file only means every file is seen for the first time
+fn load_observations(path: &Path) -> HashMap<PathBuf, Observation> {
+    read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_observations(path: &Path, observations: &HashMap<PathBuf, Observation>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(observations).map_err(io::Error::other)?;
+    let temp = path.with_extension("json.tmp");
+    write(&temp, json + "\n")?;
+    rename(&temp, path)
+}
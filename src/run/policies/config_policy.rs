@@ -11,26 +11,38 @@ pub struct ConfigPolicy {
 
 impl ConfigPolicy {
     pub fn new(config: &Config, base_dir: &Path) -> Self {
-        let includes: &Vec<PathBuf> = &config.paths.roots;
+        let include_roots = Self::normalize_paths(&config.paths.roots, base_dir);
         let mut excludes: Vec<PathBuf> = Vec::new();
 
         // base excludes
         excludes.push(config.paths.quarantine.clone());
         excludes.push(config.paths.state_dir.clone());
 
-        // rule destinations
+        // rule destinations, a relative `to` lives inside every root. Only the
+        // part before the first template token is known up front; a template
+        // that starts with a token would exclude the whole root, so it is left out.
         for rule in &config.rules {
             if let Some(action) = &rule.action {
-                excludes.push(action.to.clone());
+                let prefix = action.template.static_prefix();
+                if prefix.as_os_str().is_empty() {
+                    continue;
+                }
+                if prefix.is_relative() {
+                    for root in &include_roots {
+                        excludes.push(root.join(&prefix));
+                    }
+                } else {
+                    excludes.push(prefix);
+                }
             }
         }
         Self {
-            include_roots: Self::normalize_paths(includes, base_dir),
+            include_roots,
             exclude_roots: Self::normalize_paths(&excludes, base_dir),
         }
     }
 
-    fn lexical_normalize(path: &Path) -> PathBuf {
+    pub fn lexical_normalize(path: &Path) -> PathBuf {
         let mut anchor = PathBuf::new();
         let mut normals: Vec<OsString> = vec![];
 
@@ -29,7 +29,7 @@ pub fn should_process(policy: &EffectivePolicy, current_path: &Path) -> bool {
             return false;
         }
     }
-    return true;
+    true
 }
 
 pub fn should_descend(policy: &EffectivePolicy, current_path: &Path) -> bool {
@@ -41,5 +41,5 @@ pub fn should_descend(policy: &EffectivePolicy, current_path: &Path) -> bool {
             return false;
         }
     }
-    return true;
+    true
 }
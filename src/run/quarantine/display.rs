@@ -0,0 +1,69 @@
+use super::{QuarantineEntry, QuarantineReason};
+use chrono::DateTime;
+use colored::*;
+
+fn reason_val(r: &QuarantineReason) -> ColoredString {
+    // pad first, then color (better alignment)
+    let padded = format!("{:<8}", r.as_str());
+    match r {
+        QuarantineReason::Delete => padded.red().bold(),
+        QuarantineReason::Conflict => padded.yellow().bold(),
+    }
+}
+
+fn size_val(size: Option<u64>) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    let Some(size) = size else {
+        return "? B".to_string();
+    };
+    if size < 1000 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if value < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        unit = next;
+    }
+    format!("{:.1} {}", value, unit)
+}
+
+pub fn display_entries(entries: &[QuarantineEntry]) {
+    println!("\n{}", "QUARANTINE".bright_yellow().bold());
+    println!("{}", "══════════".bright_black());
+    if entries.is_empty() {
+        println!("{}", "the quarantine is empty".bright_black().italic());
+        return;
+    }
+
+    for entry in entries {
+        let time = DateTime::parse_from_rfc3339(&entry.quarantined_at)
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|_| entry.quarantined_at.clone());
+        println!(
+            "\n{} {} {} {}",
+            entry.id.white().bold(),
+            reason_val(&entry.reason),
+            time,
+            size_val(entry.size).bright_black()
+        );
+        println!(
+            "  {} {} {}",
+            entry.path.to_string_lossy().white(),
+            "←".bright_black(),
+            entry.original.to_string_lossy().bright_blue()
+        );
+        if let Some(rule) = entry.rule.as_deref() {
+            println!("  {} {}", "rule".bright_black(), rule);
+        }
+    }
+    let total: u64 = entries.iter().filter_map(|entry| entry.size).sum();
+    println!(
+        "\n{} files, {}",
+        entries.len().to_string().bold(),
+        size_val(Some(total))
+    );
+}
@@ -0,0 +1,433 @@
+pub mod display;
+
+use crate::run::{
+    config::patterns::build_glob,
+    executor::transfer::move_file,
+    planner::plan::{Operation, PlanAction},
+};
+use chrono::{DateTime, Local};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{
+        OpenOptions, create_dir_all, read_to_string, remove_file, rename, symlink_metadata, write,
+    },
+    io::{self, ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+// sidecar index at the top of the quarantine directory, one entry per file
+pub const INDEX_FILE: &str = ".neatd-index.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuarantineReason {
+    // a delete action, the file waits here until it is purged
+    Delete,
+    // conflict_strategy = "quarantine" moved it out of the way
+    Conflict,
+}
+
+impl QuarantineReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuarantineReason::Delete => "delete",
+            QuarantineReason::Conflict => "conflict",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    // <run_id>-<operation index>
+    pub id: String,
+    // relative to the quarantine directory
+    pub path: PathBuf,
+    pub original: PathBuf,
+    pub reason: QuarantineReason,
+    pub rule: Option<String>,
+    pub size: Option<u64>,
+    pub quarantined_at: String,
+    pub run_id: String,
+}
+
+impl QuarantineEntry {
+    pub fn new(run_id: &str, index: usize, quarantine: &Path, operation: &Operation) -> Self {
+        let destination = operation.destination.as_deref().unwrap_or(Path::new(""));
+        Self {
+            id: Self::id(run_id, index),
+            path: destination
+                .strip_prefix(quarantine)
+                .unwrap_or(destination)
+                .to_path_buf(),
+            original: operation.source.clone(),
+            reason: match operation.action {
+                PlanAction::Delete => QuarantineReason::Delete,
+                _ => QuarantineReason::Conflict,
+            },
+            rule: operation.rule.clone(),
+            size: operation.source_size,
+            quarantined_at: Local::now().to_rfc3339(),
+            run_id: run_id.to_string(),
+        }
+    }
+
+    pub fn id(run_id: &str, index: usize) -> String {
+        format!("{}-{}", run_id, index)
+    }
+}
+
+// Written before the file moves: an entry whose file never arrived is dropped
+// on read, a file without an entry could not be restored.
+pub fn append_entry(quarantine: &Path, entry: &QuarantineEntry) -> io::Result<()> {
+    let mut line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    line.push('\n');
+    let mut index = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(quarantine.join(INDEX_FILE))?;
+    index.write_all(line.as_bytes())?;
+    index.sync_data()
+}
+
+// Entries of the files still in quarantine, oldest first. A resumed run may
+// have written an id twice, the last entry wins. So does the last entry for a
+// path: an older one whose move failed would otherwise claim the newer file.
+pub fn read_index(quarantine: &Path) -> Result<Vec<QuarantineEntry>, String> {
+    let path = quarantine.join(INDEX_FILE);
+    let content = match read_to_string(&path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("cannot read {:?}: {}", path, error)),
+    };
+
+    let mut entries: Vec<QuarantineEntry> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let lines: Vec<&str> = content.lines().collect();
+    for (number, line) in lines.iter().enumerate() {
+        let entry: QuarantineEntry = match serde_json::from_str(line) {
+            Ok(entry) => entry,
+            // torn last line of an interrupted append
+            Err(_) if number + 1 == lines.len() && !content.ends_with('\n') => break,
+            Err(error) => return Err(format!("{:?} line {}: {}", path, number + 1, error)),
+        };
+        match positions.get(&entry.id) {
+            Some(&position) => entries[position] = entry,
+            None => {
+                positions.insert(entry.id.clone(), entries.len());
+                entries.push(entry);
+            }
+        }
+    }
+    entries.retain(|entry| symlink_metadata(quarantine.join(&entry.path)).is_ok());
+    let mut paths: HashSet<PathBuf> = HashSet::new();
+    let mut newest: Vec<QuarantineEntry> = entries
+        .into_iter()
+        .rev()
+        .filter(|entry| paths.insert(entry.path.clone()))
+        .collect();
+    newest.reverse();
+    Ok(newest)
+}
+
+// the whole index is rewritten aside and renamed over, like run.json
+fn write_index(quarantine: &Path, entries: &[QuarantineEntry]) -> Result<(), String> {
+    let path = quarantine.join(INDEX_FILE);
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+        content.push('\n');
+    }
+    let temp = quarantine.join(format!("{}.tmp", INDEX_FILE));
+    write(&temp, content)
+        .and_then(|()| rename(&temp, &path))
+        .map_err(|error| format!("cannot write {:?}: {}", path, error))
+}
+
+// Drops the entry of a file that left the quarantine some other way, e.g.
+// moved back by `neatd undo`. Called after the file moved.
+pub fn forget_entry(quarantine: &Path, id: &str) -> Result<(), String> {
+    let entries = read_index(quarantine)?;
+    let remaining: Vec<QuarantineEntry> =
+        entries.into_iter().filter(|entry| entry.id != id).collect();
+    write_index(quarantine, &remaining)
+}
+
+// An id restores one file, anything else is a glob matched against the
+// original path. A file never replaces what now lives at its original path.
+pub fn restore(quarantine: &Path, target: &str) -> Result<bool, String> {
+    let entries = read_index(quarantine)?;
+    let selected: Vec<&QuarantineEntry> = match entries.iter().find(|entry| entry.id == target) {
+        Some(entry) => vec![entry],
+        None => {
+            let glob = build_glob(target)?.compile_matcher();
+            entries
+                .iter()
+                .filter(|entry| glob.is_match(&entry.original))
+                .collect()
+        }
+    };
+    if selected.is_empty() {
+        return Err(format!("nothing in the quarantine matches {:?}", target));
+    }
+
+    let mut restored: HashSet<String> = HashSet::new();
+    let mut conflicts = 0;
+    let mut failed = 0;
+    for entry in selected {
+        let current = quarantine.join(&entry.path);
+        let original = entry.original.to_string_lossy();
+        if symlink_metadata(&entry.original).is_ok() {
+            conflicts += 1;
+            println!(
+                "  {} {} {}",
+                format!("{:<8}", "conflict").yellow().bold(),
+                original.white(),
+                "is occupied, left in quarantine".bright_black()
+            );
+            continue;
+        }
+        let moved = match entry.original.parent() {
+            Some(parent) => create_dir_all(parent),
+            None => Ok(()),
+        }
+        .and_then(|()| move_file(&current, &entry.original, false));
+        match moved {
+            Ok(()) => {
+                println!(
+                    "  {} {} {} {}",
+                    format!("{:<8}", "restore").green().bold(),
+                    current.to_string_lossy().white(),
+                    "→".bright_black(),
+                    original.green()
+                );
+                restored.insert(entry.id.clone());
+            }
+            Err(error) => {
+                failed += 1;
+                println!(
+                    "  {} {} {}",
+                    format!("{:<8}", "failed").red().bold(),
+                    current.to_string_lossy().white(),
+                    error
+                );
+            }
+        }
+    }
+
+    let remaining: Vec<QuarantineEntry> = entries
+        .into_iter()
+        .filter(|entry| !restored.contains(&entry.id))
+        .collect();
+    write_index(quarantine, &remaining)?;
+    println!(
+        "\n{} restored, {} conflicts, {} failed",
+        restored.len().to_string().green().bold(),
+        conflicts.to_string().yellow().bold(),
+        failed.to_string().red().bold()
+    );
+    Ok(conflicts == 0 && failed == 0)
+}
+
+// Removes files quarantined longer than `older_than` for good. Callers check
+// safety.allow_delete first.
+pub fn purge(quarantine: &Path, older_than: Duration) -> Result<bool, String> {
+    let entries = read_index(quarantine)?;
+    // a cutoff before the earliest representable time leaves nothing to purge
+    let cutoff = chrono::Duration::from_std(older_than)
+        .ok()
+        .and_then(|older_than| Local::now().checked_sub_signed(older_than));
+    let mut purged: HashSet<String> = HashSet::new();
+    let mut failed = 0;
+
+    for entry in &entries {
+        // an unreadable time is kept, purging must never guess
+        let expired = DateTime::parse_from_rfc3339(&entry.quarantined_at)
+            .is_ok_and(|quarantined_at| cutoff.is_some_and(|cutoff| quarantined_at < cutoff));
+        if !expired {
+            continue;
+        }
+        let current = quarantine.join(&entry.path);
+        match remove_file(&current) {
+            Ok(()) => {
+                println!(
+                    "  {} {} {}",
+                    format!("{:<8}", "purge").red().bold(),
+                    current.to_string_lossy().white(),
+                    format!("from {}", entry.original.to_string_lossy()).bright_black()
+                );
+                purged.insert(entry.id.clone());
+            }
+            Err(error) => {
+                failed += 1;
+                println!(
+                    "  {} {} {}",
+                    format!("{:<8}", "failed").red().bold(),
+                    current.to_string_lossy().white(),
+                    error
+                );
+            }
+        }
+    }
+
+    if !purged.is_empty() {
+        let remaining: Vec<QuarantineEntry> = entries
+            .into_iter()
+            .filter(|entry| !purged.contains(&entry.id))
+            .collect();
+        write_index(quarantine, &remaining)?;
+    }
+    println!(
+        "{} purged, {} failed",
+        purged.len().to_string().red().bold(),
+        failed.to_string().red().bold()
+    );
+    Ok(failed == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, path: &str, original: &Path, quarantined_at: &str) -> QuarantineEntry {
+        QuarantineEntry {
+            id: id.to_string(),
+            path: PathBuf::from(path),
+            original: original.to_path_buf(),
+            reason: QuarantineReason::Delete,
+            rule: None,
+            size: None,
+            quarantined_at: quarantined_at.to_string(),
+            run_id: "run".to_string(),
+        }
+    }
+
+    #[test]
+    fn newest_entry_owns_a_reused_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let quarantine = dir.path();
+        let now = Local::now().to_rfc3339();
+        // the first move failed after its entry was written, a later run put
+        // another file under the same name
+        append_entry(
+            quarantine,
+            &entry("a-0", "x.txt", Path::new("/a/x.txt"), &now),
+        )
+        .unwrap();
+        append_entry(
+            quarantine,
+            &entry("b-0", "x.txt", Path::new("/b/x.txt"), &now),
+        )
+        .unwrap();
+        write(quarantine.join("x.txt"), "b").unwrap();
+
+        let entries = read_index(quarantine).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, "b-0");
+    }
+
+    #[test]
+    fn forget_entry_drops_only_that_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let quarantine = dir.path();
+        let now = Local::now().to_rfc3339();
+        append_entry(
+            quarantine,
+            &entry("a-0", "x.txt", Path::new("/a/x.txt"), &now),
+        )
+        .unwrap();
+        append_entry(
+            quarantine,
+            &entry("a-1", "y.txt", Path::new("/a/y.txt"), &now),
+        )
+        .unwrap();
+        write(quarantine.join("x.txt"), "x").unwrap();
+        write(quarantine.join("y.txt"), "y").unwrap();
+
+        forget_entry(quarantine, "a-0").unwrap();
+        let ids: Vec<String> = read_index(quarantine)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(ids, ["a-1"]);
+    }
+
+    // a file in the quarantine with its entry, as a delete leaves it
+    fn quarantined(quarantine: &Path, id: &str, original: &Path, quarantined_at: &str) {
+        let name = original.file_name().unwrap().to_string_lossy();
+        let path = format!("{}-{}", id, name);
+        write(quarantine.join(&path), name.as_bytes()).unwrap();
+        append_entry(quarantine, &entry(id, &path, original, quarantined_at)).unwrap();
+    }
+
+    #[test]
+    fn restore_never_replaces_an_occupied_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let (quarantine, home) = (dir.path().join("quarantine"), dir.path().join("home"));
+        create_dir_all(&quarantine).unwrap();
+        create_dir_all(&home).unwrap();
+        let now = Local::now().to_rfc3339();
+        quarantined(&quarantine, "a-0", &home.join("free.txt"), &now);
+        quarantined(&quarantine, "a-1", &home.join("taken.txt"), &now);
+        write(home.join("taken.txt"), "new").unwrap();
+
+        assert_eq!(restore(&quarantine, "**/*.txt"), Ok(false));
+        assert_eq!(read_to_string(home.join("free.txt")).unwrap(), "free.txt");
+        assert_eq!(read_to_string(home.join("taken.txt")).unwrap(), "new");
+        let ids: Vec<String> = read_index(&quarantine)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(ids, ["a-1"]);
+    }
+
+    #[test]
+    fn restore_selects_by_id_or_glob() {
+        let dir = tempfile::tempdir().unwrap();
+        let (quarantine, home) = (dir.path().join("quarantine"), dir.path().join("home"));
+        create_dir_all(&quarantine).unwrap();
+        let now = Local::now().to_rfc3339();
+        quarantined(&quarantine, "a-0", &home.join("a.pdf"), &now);
+        quarantined(&quarantine, "a-1", &home.join("b.pdf"), &now);
+        quarantined(&quarantine, "a-2", &home.join("c.txt"), &now);
+
+        assert_eq!(restore(&quarantine, "a-2"), Ok(true));
+        assert!(home.join("c.txt").exists());
+        assert!(!home.join("a.pdf").exists());
+        assert!(restore(&quarantine, "**/*.zip").is_err());
+        assert_eq!(restore(&quarantine, "**/*.pdf"), Ok(true));
+        assert!(home.join("a.pdf").exists() && home.join("b.pdf").exists());
+        assert!(read_index(&quarantine).unwrap().is_empty());
+    }
+
+    #[test]
+    fn purge_removes_only_expired_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let quarantine = dir.path();
+        let old = (Local::now() - chrono::Duration::days(40)).to_rfc3339();
+        let new = Local::now().to_rfc3339();
+        quarantined(quarantine, "a-0", Path::new("/home/old.txt"), &old);
+        quarantined(quarantine, "a-1", Path::new("/home/new.txt"), &new);
+        quarantined(quarantine, "a-2", Path::new("/home/odd.txt"), "not a time");
+
+        // a cutoff before the earliest representable time purges nothing
+        assert_eq!(purge(quarantine, Duration::MAX), Ok(true));
+        assert_eq!(read_index(quarantine).unwrap().len(), 3);
+
+        assert_eq!(
+            purge(quarantine, Duration::from_secs(30 * 86_400)),
+            Ok(true)
+        );
+        let ids: Vec<String> = read_index(quarantine)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.id)
+            .collect();
+        assert_eq!(ids, ["a-1", "a-2"]);
+        assert!(!quarantine.join("a-0-old.txt").exists());
+    }
+}
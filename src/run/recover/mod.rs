@@ -0,0 +1,423 @@
+use crate::run::{
+    config::config::Config,
+    entries::fs_entry::EntryError,
+    executor::{
+        execute_plan,
+        transfer::{hash_file, temp_path},
+        verify::verify_sources,
+    },
+    journal::{
+        Journal, PLAN_FILE, latest_records, list_runs, read_journal, read_run_info,
+        record::{JournalRecord, RecordStatus, RunMode},
+        runs_dir,
+    },
+    planner::{
+        plan::PlanAction, plan_file::load_plan, protected::enforce_protected, roots::enforce_roots,
+    },
+    undo::{UndoFilter, undo_run},
+};
+use chrono::Local;
+use colored::*;
+use std::{
+    env::current_dir,
+    fs::{remove_file, symlink_metadata},
+    path::Path,
+};
+
+// what to keep when a cross-device move died with both copies on disk
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Keep {
+    Destination,
+    Source,
+}
+
+// what an in-flight operation turned out to have done
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Reconciled {
+    Committed,
+    // source and destination are both gone
+    Lost,
+    // both exist and nothing tells whether the destination is the copy
+    Conflict,
+}
+
+// runs without finished_at in their run.json, oldest first
+pub fn incomplete_runs(state_dir: &Path) -> Result<Vec<String>, String> {
+    let mut incomplete: Vec<String> = Vec::new();
+    for run_id in list_runs(state_dir)? {
+        let info = read_run_info(&runs_dir(state_dir).join(&run_id))?;
+        if info.finished_at.is_none() {
+            incomplete.push(run_id);
+        }
+    }
+    Ok(incomplete)
+}
+
+// New runs wait until an interrupted one is resumed or rolled back, otherwise
+// two runs would fight over the same files. Returns whether one is blocking.
+pub fn report_incomplete(state_dir: &Path) -> bool {
+    let incomplete = match incomplete_runs(state_dir) {
+        Ok(incomplete) => incomplete,
+        Err(error) => {
+            eprintln!("{}", error);
+            return true;
+        }
+    };
+    for run_id in &incomplete {
+        eprintln!(
+            "{} {}",
+            "Run was interrupted:".red().bold(),
+            run_id.white().bold()
+        );
+        eprintln!(
+            "  {} neatd resume {}   finish the remaining operations",
+            "•".red().bold(),
+            run_id
+        );
+        eprintln!(
+            "  {} neatd rollback {} undo the operations already done",
+            "•".red().bold(),
+            run_id
+        );
+    }
+    !incomplete.is_empty()
+}
+
+pub fn resume_run(config: &Config, state_dir: &Path, run_id: Option<&str>) -> Result<bool, String> {
+    let run_id = pick_run(state_dir, run_id)?;
+    let mut journal = Journal::reopen(state_dir, &run_id)?;
+    if journal.mode() == RunMode::Undo {
+        return Err(format!(
+            "run {} is an undo, it can only be rolled back: neatd rollback {}",
+            run_id, run_id
+        ));
+    }
+    println!(
+        "\n{} {}",
+        "RESUME".bright_yellow().bold(),
+        run_id.white().bold()
+    );
+    reconcile_in_flight(&mut journal, Keep::Destination)?;
+
+    let mut operations = load_plan(&journal.dir.join(PLAN_FILE))?.operations;
+    let records = latest_records(read_journal(&journal.dir)?);
+    // done or failed for good; still pending means it never took effect
+    for record in records {
+        if record.status != RecordStatus::Pending
+            && let Some(operation) = operations.get_mut(record.index)
+        {
+            operation.action = PlanAction::Skip;
+        }
+    }
+    // the config may have changed since the run was planned
+    enforce_roots(config, &current_dir().unwrap_or_default(), &mut operations);
+    enforce_protected(config, &mut operations);
+
+    let stale = verify_sources(&operations);
+    if !stale.is_empty() {
+        return Err(format!(
+            "the remaining operations no longer match the filesystem, nothing was resumed:\n  {}",
+            stale.join("\n  ")
+        ));
+    }
+    let errors = execute_plan(&operations, &mut journal);
+    let dir = journal.dir.clone();
+    journal
+        .finish()
+        .map_err(|error| format!("cannot finish the run in {:?}: {}", dir, error))?;
+    Ok(!errors.iter().any(EntryError::is_failure))
+}
+
+pub fn rollback_run(state_dir: &Path, run_id: Option<&str>) -> Result<bool, String> {
+    let run_id = pick_run(state_dir, run_id)?;
+    let mut journal = Journal::reopen(state_dir, &run_id)?;
+    println!(
+        "\n{} {}",
+        "ROLLBACK".bright_yellow().bold(),
+        run_id.white().bold()
+    );
+    reconcile_in_flight(&mut journal, Keep::Source)?;
+    // closed first, the undo below is a run of its own
+    let dir = journal.dir.clone();
+    journal
+        .finish()
+        .map_err(|error| format!("cannot finish the run in {:?}: {}", dir, error))?;
+
+    let filter = UndoFilter {
+        rule: None,
+        glob: None,
+    };
+    undo_run(state_dir, Some(&run_id), &filter)
+}
+
+fn pick_run(state_dir: &Path, run_id: Option<&str>) -> Result<String, String> {
+    let incomplete = incomplete_runs(state_dir)?;
+    match run_id {
+        Some(run_id) if incomplete.iter().any(|candidate| candidate == run_id) => {
+            Ok(run_id.to_string())
+        }
+        Some(run_id) => Err(format!("run {} is not an interrupted run", run_id)),
+        None => incomplete
+            .last()
+            .cloned()
+            .ok_or_else(|| "no interrupted runs, nothing to recover".to_string()),
+    }
+}
+
+// An operation whose last record is pending was in flight when the process
+// died. Its files are inspected and the outcome is written to the journal;
+// an operation that never took effect stays pending.
+fn reconcile_in_flight(journal: &mut Journal, keep: Keep) -> Result<(), String> {
+    let in_flight: Vec<JournalRecord> = latest_records(read_journal(&journal.dir)?)
+        .into_iter()
+        .filter(|record| record.status == RecordStatus::Pending)
+        .collect();
+
+    for record in in_flight {
+        let Some(reconciled) = reconcile(&record, keep) else {
+            println!(
+                "  {} {} {}",
+                format!("{:<9}", "pending").bright_black(),
+                record.source.to_string_lossy().white(),
+                "never took effect".bright_black()
+            );
+            continue;
+        };
+        let mut outcome = record.clone();
+        outcome.timestamp = Local::now().to_rfc3339();
+        outcome.status = match reconciled {
+            Reconciled::Committed => RecordStatus::Committed,
+            Reconciled::Lost | Reconciled::Conflict => RecordStatus::Failed,
+        };
+        outcome.error = match reconciled {
+            Reconciled::Committed => None,
+            Reconciled::Lost => {
+                Some("interrupted, both source and destination are gone".to_string())
+            }
+            Reconciled::Conflict => Some(
+                "interrupted, the destination may not be the copy, both were left in place"
+                    .to_string(),
+            ),
+        };
+        journal
+            .append(&outcome)
+            .map_err(|error| format!("cannot write {:?}: {}", journal.path(), error))?;
+        println!(
+            "  {} {} {}",
+            format!("{:<9}", "reconcile").yellow().bold(),
+            record.source.to_string_lossy().white(),
+            match reconciled {
+                Reconciled::Committed => "completed before the interruption".bright_black(),
+                Reconciled::Lost => "lost, see the journal".red(),
+                Reconciled::Conflict => "conflict, both files were left in place".red(),
+            }
+        );
+    }
+    Ok(())
+}
+
+// None when the operation never took effect (and can simply run again)
+fn reconcile(record: &JournalRecord, keep: Keep) -> Option<Reconciled> {
+    let destination = record.destination.as_deref()?;
+    // a partial copy only ever lives under the temp name
+    let _ = remove_file(temp_path(destination));
+
+    let source_exists = symlink_metadata(&record.source).is_ok();
+    let destination_exists = symlink_metadata(destination).is_ok();
+    // The destination may have existed before (overwrite), only the content
+    // tells. Without a journaled hash a destination that differs in size or
+    // mtime is certainly not the copy, one that agrees is taken for it; None
+    // when the two cannot be told apart.
+    let copied = match (record.hash.as_ref(), hash_file(destination)) {
+        (Some(hash), Ok(current)) => Some(current.to_hex().as_str() == hash),
+        (None, Ok(_)) if same_state(&record.source, destination, record.size) => Some(true),
+        (None, Ok(_)) => None,
+        (_, Err(_)) => Some(false),
+    };
+
+    match record.action {
+        // a delete is a move into quarantine
+        PlanAction::Move | PlanAction::Delete if !source_exists && destination_exists => {
+            Some(Reconciled::Committed)
+        }
+        PlanAction::Move | PlanAction::Delete if !source_exists => Some(Reconciled::Lost),
+        // cross-device move that died between the copy and removing the source
+        PlanAction::Move | PlanAction::Delete if copied == Some(true) => match keep {
+            Keep::Destination => remove_file(&record.source)
+                .ok()
+                .map(|()| Reconciled::Committed),
+            Keep::Source => {
+                let _ = remove_file(destination);
+                None
+            }
+        },
+        PlanAction::Copy if copied == Some(true) => Some(Reconciled::Committed),
+        // neither file is removed on a guess
+        PlanAction::Move | PlanAction::Delete | PlanAction::Copy if copied.is_none() => {
+            Some(Reconciled::Conflict)
+        }
+        _ => None,
+    }
+}
+
+// a finished copy has the size of the source and, set by the copy, its mtime
+fn same_state(source: &Path, destination: &Path, size: Option<u64>) -> bool {
+    let (Ok(source), Ok(destination)) = (symlink_metadata(source), symlink_metadata(destination))
+    else {
+        return false;
+    };
+    let modified = destination.modified().ok();
+    size.is_none_or(|size| size == destination.len())
+        && source.len() == destination.len()
+        && modified.is_some()
+        && modified == source.modified().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config_file_data::config_file_data,
+        run::{config::validate::validate_config, planner::plan::Operation},
+    };
+    use std::{
+        fs::{File, create_dir_all, read_to_string, rename, write},
+        path::PathBuf,
+    };
+
+    struct Interrupted {
+        _dir: tempfile::TempDir,
+        state: PathBuf,
+        root: PathBuf,
+    }
+
+    impl Interrupted {
+        fn new(names: &[&str]) -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let (state, root) = (dir.path().join("state"), dir.path().join("root"));
+            create_dir_all(root.join("docs")).unwrap();
+            for name in names {
+                write(root.join(name), *name).unwrap();
+            }
+            Self {
+                _dir: dir,
+                state,
+                root,
+            }
+        }
+
+        fn source(&self, name: &str) -> PathBuf {
+            self.root.join(name)
+        }
+
+        fn destination(&self, name: &str) -> PathBuf {
+            self.root.join("docs").join(name)
+        }
+
+        // a run that died with every operation still pending, hashed or not
+        fn journal(&self, names: &[&str], hashed: bool) {
+            let operations: Vec<Operation> = names
+                .iter()
+                .map(|name| {
+                    Operation::planned(
+                        PlanAction::Move,
+                        self.source(name),
+                        self.root.clone(),
+                        Some(self.destination(name)),
+                    )
+                })
+                .collect();
+            let mut journal = Journal::start(&self.state, RunMode::Run, &operations).unwrap();
+            for (index, operation) in operations.iter().enumerate() {
+                let hash =
+                    hashed.then(|| hash_file(&operation.source).unwrap().to_hex().to_string());
+                let record = JournalRecord::new(
+                    &journal.run_id,
+                    index,
+                    operation,
+                    hash,
+                    RecordStatus::Pending,
+                );
+                journal.append(&record).unwrap();
+            }
+        }
+
+        // what a cross-device move leaves when it dies before removing the source
+        fn copy_over(&self, name: &str) {
+            write(self.destination(name), name).unwrap();
+            let modified = self.source(name).metadata().unwrap().modified().unwrap();
+            File::options()
+                .write(true)
+                .open(self.destination(name))
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+
+        fn config(&self) -> Config {
+            let mut config: Config = toml::from_str(&config_file_data()).unwrap();
+            config.paths.roots = vec![self.root.clone()];
+            validate_config(&mut config).unwrap();
+            config
+        }
+    }
+
+    #[test]
+    fn resume_completes_what_was_in_flight() {
+        let run = Interrupted::new(&["done.txt", "copied.txt", "untouched.txt"]);
+        run.journal(&["done.txt", "copied.txt", "untouched.txt"], true);
+        rename(run.source("done.txt"), run.destination("done.txt")).unwrap();
+        run.copy_over("copied.txt");
+
+        assert_eq!(resume_run(&run.config(), &run.state, None), Ok(true));
+        for name in ["done.txt", "copied.txt", "untouched.txt"] {
+            assert!(!run.source(name).exists(), "{}", name);
+            assert_eq!(read_to_string(run.destination(name)).unwrap(), name);
+        }
+        assert!(incomplete_runs(&run.state).unwrap().is_empty());
+    }
+
+    #[test]
+    fn rollback_puts_back_what_was_in_flight() {
+        let run = Interrupted::new(&["done.txt", "copied.txt", "untouched.txt"]);
+        run.journal(&["done.txt", "copied.txt", "untouched.txt"], true);
+        rename(run.source("done.txt"), run.destination("done.txt")).unwrap();
+        run.copy_over("copied.txt");
+
+        assert_eq!(rollback_run(&run.state, None), Ok(true));
+        for name in ["done.txt", "copied.txt", "untouched.txt"] {
+            assert_eq!(read_to_string(run.source(name)).unwrap(), name);
+            assert!(!run.destination(name).exists(), "{}", name);
+        }
+        assert!(incomplete_runs(&run.state).unwrap().is_empty());
+    }
+
+    #[test]
+    fn without_a_hash_only_a_matching_copy_is_trusted() {
+        let run = Interrupted::new(&["copied.txt", "other.txt"]);
+        run.journal(&["copied.txt", "other.txt"], false);
+        // same size and mtime as the source: the finished copy
+        run.copy_over("copied.txt");
+        // another size: nothing says which file is the one to keep
+        write(run.destination("other.txt"), "someone else's").unwrap();
+
+        assert_eq!(resume_run(&run.config(), &run.state, None), Ok(true));
+        assert!(!run.source("copied.txt").exists());
+        assert_eq!(
+            read_to_string(run.destination("copied.txt")).unwrap(),
+            "copied.txt"
+        );
+        assert_eq!(
+            read_to_string(run.source("other.txt")).unwrap(),
+            "other.txt"
+        );
+        assert_eq!(
+            read_to_string(run.destination("other.txt")).unwrap(),
+            "someone else's"
+        );
+        let run_id = list_runs(&run.state).unwrap().remove(0);
+        let records = latest_records(read_journal(&runs_dir(&run.state).join(run_id)).unwrap());
+        assert_eq!(records[0].status, RecordStatus::Committed);
+        assert_eq!(records[1].status, RecordStatus::Failed);
+    }
+}
@@ -0,0 +1,87 @@
+use super::Scan;
+use crate::run::entries::fs_entry::{EntryError, FileKind, Severity};
+use colored::*;
+
+fn kind_val(k: &FileKind) -> ColoredString {
+    // pad first, then color (better alignment)
+    match k {
+        FileKind::File => format!("{:<8}", "file").white(),
+        FileKind::Directory => format!("{:<8}", "dir").bright_blue(),
+        FileKind::Symlink => format!("{:<8}", "symlink").cyan(),
+        FileKind::Other => format!("{:<8}", "other").bright_black(),
+    }
+}
+
+fn severity_val(s: &Severity) -> ColoredString {
+    match s {
+        Severity::Warning => "warning".yellow().bold(),
+        Severity::Error => "error".red().bold(),
+        Severity::Fatal => "fatal".bright_red().bold(),
+    }
+}
+
+pub fn display_error(error: &EntryError) {
+    let source = match &error.source {
+        Some(source) => source.to_string(),
+        None => String::new(),
+    };
+    println!(
+        "  {} {:?} {} {} {}",
+        severity_val(&error.severity),
+        error.operation,
+        error.path.to_string_lossy().white(),
+        source.bright_black(),
+        format!("({:?})", error.outcome).bright_black()
+    );
+}
+
+pub fn display_scan(scan: &Scan) {
+    println!("\n{}", "SCAN".bright_yellow().bold());
+    println!("{}", "════".bright_black());
+
+    let mut current_root = None;
+    for entry in &scan.entries {
+        if current_root != Some(&entry.root) {
+            println!("\n{}", entry.root.to_string_lossy().bright_blue().bold());
+            current_root = Some(&entry.root);
+        }
+        let relative = entry.path.strip_prefix(&entry.root).unwrap_or(&entry.path);
+        let detail = match (&entry.skip, &entry.matched) {
+            (Some(reason), _) => format!("skipped: {}", reason),
+            (None, Some(matched)) => {
+                let action = match &matched.action {
+                    Some(action) => format!("{:?}", action.r#type).to_lowercase(),
+                    None => "no action".to_string(),
+                };
+                format!(
+                    "→ {} [{}] {} ({})",
+                    matched.name, matched.priority, action, matched.reason
+                )
+            }
+            (None, None) => "no rule matched".to_string(),
+        };
+        println!(
+            "  {} {} {}",
+            kind_val(&entry.kind),
+            relative.to_string_lossy().green(),
+            detail.bright_black()
+        );
+        for error in &entry.errors {
+            display_error(error);
+        }
+    }
+
+    if !scan.errors.is_empty() {
+        println!("\n{}", "ERRORS".red().bold());
+        for error in &scan.errors {
+            display_error(error);
+        }
+    }
+
+    let entry_errors: usize = scan.entries.iter().map(|e| e.errors.len()).sum();
+    println!(
+        "\n{} entries, {} errors",
+        scan.entries.len().to_string().cyan().bold(),
+        (scan.errors.len() + entry_errors).to_string().red().bold()
+    );
+}
@@ -0,0 +1,42 @@
+use crate::run::{
+    config::{config::Ignore, patterns::normalize_extension},
+    entries::fs_entry::SkipReason,
+};
+use std::path::Path;
+
+// `relative` is the entry path relative to the include root it was found under,
+// so globs like "**/.git/**" behave the same for every root.
+pub fn check_ignore(ignore: &Ignore, relative: &Path, is_dir: bool) -> Option<SkipReason> {
+    if ignore.ignore_hidden && is_hidden(relative) {
+        return Some(SkipReason::Hidden);
+    }
+
+    if is_dir {
+        let matched = ignore.compiled.dir_globs.matches(relative);
+        if let Some(index) = matched.first() {
+            let source = ignore.compiled.dir_glob_sources[*index];
+            return Some(SkipReason::IgnoredGlob(ignore.globs[source].clone()));
+        }
+    }
+
+    // the first pattern in config order wins, so the reported glob is stable
+    if let Some(index) = ignore.compiled.globs.matches(relative).first() {
+        return Some(SkipReason::IgnoredGlob(ignore.globs[*index].clone()));
+    }
+
+    if !is_dir && let Some(extension) = relative.extension().and_then(|e| e.to_str()) {
+        let extension = normalize_extension(extension);
+        if ignore.compiled.extensions.contains(&extension) {
+            return Some(SkipReason::IgnoredExtension(extension));
+        }
+    }
+
+    None
+}
+
+fn is_hidden(relative: &Path) -> bool {
+    relative
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
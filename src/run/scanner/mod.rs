@@ -1,74 +1,150 @@
+pub mod display;
+pub mod ignore_filter;
 pub mod log;
 pub mod process_file;
 pub mod set_error;
 pub mod set_kind;
+pub mod set_metadata;
 
 use crate::run::{
     config::config::Config,
-    entries::fs_entry::{EntryError, EntryOp, FileKind, FsEntry, Outcome, Severity},
+    entries::fs_entry::{EntryError, EntryOp, FsEntry, Severity},
     policies::{
         effective_policy::EffectivePolicy, setup_policy_for_walk, should_descend, should_process,
     },
-    scanner::{log::Log, set_error::parse_errors, set_kind::set_entry_kind},
+    scanner::{
+        ignore_filter::check_ignore, log::Log, process_file::apply_rules_to_file,
+        set_error::parse_errors, set_kind::set_entry_kind, set_metadata::set_entry_metadata,
+    },
 };
 use std::{
-    fs::read_dir,
-    path::{Path, PathBuf},
+    fs::{DirEntry, read_dir},
+    path::Path,
 };
 
-pub fn walk_policy_setup(config: &Config, base_dir: &Path) {
+pub struct Scan {
+    pub entries: Vec<FsEntry>,
+    // errors that are not tied to a single entry (unreadable directories etc.)
+    pub errors: Vec<EntryError>,
+}
+
+pub fn walk_policy_setup(config: &Config, base_dir: &Path) -> Scan {
     let effective_policy: EffectivePolicy = setup_policy_for_walk(config, base_dir);
-    for path in &effective_policy.effective_includes {
-        recurse_dirs(&effective_policy, path, config);
-    }
+    scan_roots(&effective_policy, config)
 }
 
-fn recurse_dirs(effective_policy: &EffectivePolicy, path: &Path, config: &Config) -> Log {
-    let mut fs_entry: FsEntry = FsEntry {
-        path: path.to_path_buf(),
-        kind: FileKind::Other,
+pub fn scan_roots(effective_policy: &EffectivePolicy, config: &Config) -> Scan {
+    let mut scan = Scan {
+        entries: Vec::new(),
         errors: Vec::new(),
-        metadata: None,
     };
+    for root in &effective_policy.effective_includes {
+        recurse_dirs(effective_policy, root, root, config, &mut scan);
+    }
+    scan
+}
 
-    for entry in read_dir(&path).expect("Cannot read the directories from the given path") {
-        match entry {
-            Ok(entry) => {
-                let entry_path: PathBuf = entry.path();
+fn recurse_dirs(
+    effective_policy: &EffectivePolicy,
+    root: &Path,
+    path: &Path,
+    config: &Config,
+    scan: &mut Scan,
+) -> Log {
+    if !should_descend(effective_policy, path) {
+        return Log::CannotDescend;
+    }
 
-                set_entry_kind(&mut fs_entry, &entry_path);
+    let dir_entries = match read_dir(path) {
+        Ok(dir_entries) => dir_entries,
+        Err(error) => {
+            scan.errors.push(parse_errors(
+                path,
+                EntryOp::ReadDir,
+                Some(error),
+                Severity::Error,
+            ));
+            return Log::UnknownError;
+        }
+    };
 
-                if entry_path.is_dir() {
-                    if should_descend(effective_policy, &entry_path) {
-                        return recurse_dirs(effective_policy, path, config);
-                    }
+    let mut children: Vec<DirEntry> = Vec::new();
+    for entry in dir_entries {
+        match entry {
+            Ok(entry) => children.push(entry),
+            Err(error) => {
+                scan.errors.push(parse_errors(
+                    path,
+                    EntryOp::ReadDir,
+                    Some(error),
+                    Severity::Error,
+                ));
+            }
+        }
+    }
 
-                    fs_entry.errors.push(parse_errors(&entry_path, None));
+    // read_dir order is platform dependent, sort so every scan yields the same order
+    children.sort_by_key(|child| child.file_name());
 
-                    return Log::CannotDescend;
-                } else {
-                    if should_process(effective_policy, &entry_path) {
-                        return Log::Success;
-                    }
+    for child in children {
+        visit_entry(effective_policy, root, &child, config, scan);
+    }
+    Log::Success
+}
 
-                    fs_entry.errors.push(parse_errors(&entry_path, None));
+fn visit_entry(
+    effective_policy: &EffectivePolicy,
+    root: &Path,
+    child: &DirEntry,
+    config: &Config,
+    scan: &mut Scan,
+) -> Log {
+    let entry_path = child.path();
+    let file_type = match child.file_type() {
+        Ok(file_type) => file_type,
+        Err(error) => {
+            scan.errors.push(parse_errors(
+                &entry_path,
+                EntryOp::Metadata,
+                Some(error),
+                Severity::Error,
+            ));
+            return Log::UnknownError;
+        }
+    };
 
-                    return Log::CannotProcess;
-                }
-            }
+    let relative = entry_path
+        .strip_prefix(root)
+        .unwrap_or(&entry_path)
+        .to_path_buf();
 
-            Err(error) => {
-                let entry_error = EntryError {
-                    path: path.to_path_buf(),
-                    operation: EntryOp::ParsePath,
-                    source: Some(error),
-                    severity: Severity::Error,
-                    outcome: Outcome::Skipped,
-                };
-                fs_entry.errors.push(parse_errors(path, Some(entry_error)));
-                return Log::UnknownError;
-            }
+    // symlinked directories are never followed, this keeps the walk free of cycles
+    if file_type.is_dir() {
+        if !config.general.recursive {
+            return Log::Success;
+        }
+        // an ignored directory is recorded once instead of walking its whole subtree
+        if let Some(reason) = check_ignore(&config.ignore, &relative, true) {
+            let mut fs_entry = FsEntry::new(entry_path, root.to_path_buf());
+            set_entry_kind(&mut fs_entry, &file_type);
+            fs_entry.skip = Some(reason);
+            scan.entries.push(fs_entry);
+            return Log::CannotDescend;
         }
+        return recurse_dirs(effective_policy, root, &entry_path, config, scan);
+    }
+
+    if !should_process(effective_policy, &entry_path) {
+        return Log::CannotProcess;
+    }
+
+    let mut fs_entry = FsEntry::new(entry_path, root.to_path_buf());
+    set_entry_kind(&mut fs_entry, &file_type);
+    fs_entry.skip = check_ignore(&config.ignore, &relative, false);
+    if fs_entry.skip.is_none() {
+        set_entry_metadata(&mut fs_entry);
+        apply_rules_to_file(config, &mut fs_entry);
     }
-    return Log::UnknownError;
+    scan.entries.push(fs_entry);
+    Log::Success
 }
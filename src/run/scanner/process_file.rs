@@ -1,42 +1,269 @@
-use std::{
-    collections::HashSet,
-    default,
-    path::{self, Path, PathBuf},
-};
+use std::{path::Path, time::SystemTime};
 
 use crate::run::{
-    config::config::{Config, Match, Rule},
-    entries::fs_entry::FsEntry,
+    config::{
+        config::{Config, Match, Rule},
+        patterns::normalize_extension,
+    },
+    entries::fs_entry::{FileKind, FsEntry, MatchedRule},
 };
 
 pub fn apply_rules_to_file(config: &Config, entry: &mut FsEntry) {
-    // for the current entry
-    // based on the priority of rules apply each rule.
-    // before priority you need to check if the rule matches with the entry
-    let mut matched_rules = Vec::new();
-    let path = &entry.path.to_string_lossy().to_ascii_lowercase();
-    let match_default = Match::new();
-    for rule in &config.rules {
-        let available_extensions = rule
-            .r#match
-            .as_ref()
-            .unwrap_or(&match_default)
-            .extensions
-            .as_deref();
-        let available_extensions_hash_set = get_hash_set(available_extensions.unwrap_or(&[]));
-        if available_extensions_hash_set.contains(path) {
-            matched_rules.push(rule);
-        }
-    }
-    // once you get the matched rules..
-    // sort based on priority
-    // after sorting update entry to contain the process that needs to be done.
-}
-
-pub fn get_hash_set(vec_of_strings: &[String]) -> HashSet<String> {
-    let mut hash_set: HashSet<String> = HashSet::new();
-    for val in vec_of_strings {
-        hash_set.insert(val.clone().to_ascii_lowercase());
-    }
-    hash_set
+    // ignored entries never reach the rule engine, nor does anything but a
+    // file or a symlink: opening a FIFO or a device would block the executor
+    if entry.skip.is_some() || !matches!(entry.kind, FileKind::File | FileKind::Symlink) {
+        return;
+    }
+
+    // rules are pre-sorted by priority when the config is loaded,
+    // so the first rule that matches is the winner.
+    for rule in config.rules_by_priority() {
+        if let Ok(reason) = evaluate_rule(rule, entry) {
+            entry.matched = Some(MatchedRule {
+                name: rule.name.clone(),
+                priority: rule.priority,
+                reason,
+                action: rule.action.clone(),
+            });
+            return;
+        }
+    }
+}
+
+// Ok holds why the rule matched, Err why it did not.
+pub fn evaluate_rule(rule: &Rule, entry: &FsEntry) -> Result<String, String> {
+    if !rule.enabled {
+        return Err("rule is disabled".to_string());
+    }
+    match rule.r#match.as_ref() {
+        Some(rule_match) => evaluate_match(rule_match, entry),
+        None => Err("rule has no match section".to_string()),
+    }
+}
+
+// Every criterion that is set must hold; evaluation stops at the first one that
+// fails. `any = true` is one criterion that always holds, `not`, `all` and
+// `any_of` next to it still apply.
+pub fn evaluate_match(rule_match: &Match, entry: &FsEntry) -> Result<String, String> {
+    let compiled = &rule_match.compiled;
+    let relative = entry.path.strip_prefix(&entry.root).unwrap_or(&entry.path);
+    let name = entry
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let mut reasons: Vec<String> = Vec::new();
+
+    if rule_match.any.unwrap_or(false) {
+        reasons.push("any = true".to_string());
+    }
+
+    if !compiled.extensions.is_empty() {
+        match entry_extension(&entry.path) {
+            Some(extension) if compiled.extensions.contains(&extension) => {
+                reasons.push(format!("extension {:?}", extension));
+            }
+            Some(extension) => {
+                return Err(format!("extension {:?} not in extensions", extension));
+            }
+            None => return Err("file has no extension".to_string()),
+        }
+    }
+
+    if let Some(name_globs) = compiled.name_globs.as_ref() {
+        if !name_globs.is_match(name.as_ref()) {
+            return Err(format!("name {:?} does not match name_globs", name));
+        }
+        reasons.push(format!("name {:?} matches name_globs", name));
+    }
+
+    if let Some(path_globs) = compiled.path_globs.as_ref() {
+        if !path_globs.is_match(relative) {
+            return Err(format!("path {:?} does not match path_globs", relative));
+        }
+        reasons.push(format!("path {:?} matches path_globs", relative));
+    }
+
+    if let Some(name_regex) = compiled.name_regex.as_ref() {
+        if !name_regex.is_match(&name) {
+            return Err(format!("name {:?} does not match name_regex", name));
+        }
+        reasons.push(format!("name {:?} matches name_regex", name));
+    }
+
+    if let (Some(under), Some(written)) = (compiled.under.as_ref(), rule_match.under.as_ref()) {
+        if !relative.starts_with(under) {
+            return Err(format!("path {:?} is not under {:?}", relative, written));
+        }
+        reasons.push(format!("under {:?}", written));
+    }
+
+    check_size(rule_match, entry, &mut reasons)?;
+    check_age(rule_match, entry, &mut reasons)?;
+    // nested trees last, the flat criteria above are cheaper to reject on
+    check_combinators(rule_match, entry, &mut reasons)?;
+
+    if reasons.is_empty() {
+        return Err("match has no criteria".to_string());
+    }
+    Ok(reasons.join(", "))
+}
+
+fn check_size(
+    rule_match: &Match,
+    entry: &FsEntry,
+    reasons: &mut Vec<String>,
+) -> Result<(), String> {
+    let compiled = &rule_match.compiled;
+    if compiled.min_size.is_none() && compiled.max_size.is_none() {
+        return Ok(());
+    }
+    let size = entry
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.size_bytes)
+        .ok_or("file size is unknown")?;
+
+    // reasons quote the limits as written in the config, e.g. "500MB"
+    if let (Some(min_size), Some(written)) = (compiled.min_size, &rule_match.min_size) {
+        if size < min_size {
+            return Err(format!("size {} B is below min_size {}", size, written));
+        }
+        reasons.push(format!("size {} B >= {}", size, written));
+    }
+    if let (Some(max_size), Some(written)) = (compiled.max_size, &rule_match.max_size) {
+        if size > max_size {
+            return Err(format!("size {} B is above max_size {}", size, written));
+        }
+        reasons.push(format!("size {} B <= {}", size, written));
+    }
+    Ok(())
+}
+
+fn check_age(rule_match: &Match, entry: &FsEntry, reasons: &mut Vec<String>) -> Result<(), String> {
+    let compiled = &rule_match.compiled;
+    if compiled.older_than.is_none() && compiled.newer_than.is_none() {
+        return Ok(());
+    }
+    let modified = entry
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.modified)
+        .ok_or("modification time is unknown")?;
+    // a timestamp in the future counts as brand new
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+
+    if let (Some(older_than), Some(written)) = (compiled.older_than, &rule_match.older_than) {
+        if age < older_than {
+            return Err(format!(
+                "modified {}s ago, not older than {}",
+                age.as_secs(),
+                written
+            ));
+        }
+        reasons.push(format!("older than {}", written));
+    }
+    if let (Some(newer_than), Some(written)) = (compiled.newer_than, &rule_match.newer_than) {
+        if age >= newer_than {
+            return Err(format!(
+                "modified {}s ago, not newer than {}",
+                age.as_secs(),
+                written
+            ));
+        }
+        reasons.push(format!("newer than {}", written));
+    }
+    Ok(())
+}
+
+fn check_combinators(
+    rule_match: &Match,
+    entry: &FsEntry,
+    reasons: &mut Vec<String>,
+) -> Result<(), String> {
+    if let Some(all) = rule_match.all.as_ref() {
+        let mut matched: Vec<String> = Vec::new();
+        for (index, child) in all.iter().enumerate() {
+            let reason = evaluate_match(child, entry)
+                .map_err(|reason| format!("all[{}] failed: {}", index, reason))?;
+            matched.push(reason);
+        }
+        reasons.push(format!("all({})", matched.join("; ")));
+    }
+
+    if let Some(any_of) = rule_match.any_of.as_ref() {
+        let mut failed: Vec<String> = Vec::new();
+        let mut matched = None;
+        for (index, child) in any_of.iter().enumerate() {
+            match evaluate_match(child, entry) {
+                Ok(reason) => {
+                    matched = Some(format!("any_of[{}]({})", index, reason));
+                    break;
+                }
+                Err(reason) => failed.push(reason),
+            }
+        }
+        match matched {
+            Some(reason) => reasons.push(reason),
+            None => return Err(format!("no any_of matched ({})", failed.join("; "))),
+        }
+    }
+
+    if let Some(not) = rule_match.not.as_ref() {
+        match evaluate_match(not, entry) {
+            Ok(reason) => return Err(format!("not failed, inner match holds: {}", reason)),
+            Err(reason) => reasons.push(format!("not({})", reason)),
+        }
+    }
+    Ok(())
+}
+
+pub fn entry_extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(normalize_extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config_file_data::config_file_data, run::config::validate::validate_config,
+        run::scanner::set_kind::set_entry_kind,
+    };
+    use std::fs::{symlink_metadata, write};
+
+    fn scanned(path: &Path) -> FsEntry {
+        let mut entry = FsEntry::new(path.to_path_buf(), path.parent().unwrap().to_path_buf());
+        set_entry_kind(&mut entry, &symlink_metadata(path).unwrap().file_type());
+        entry
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fifo_gets_no_match() {
+        // the default config ends in a catch-all `any = true` rule
+        let mut config: Config = toml::from_str(&config_file_data()).unwrap();
+        validate_config(&mut config).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        let fifo = dir.path().join("pipe.txt");
+        write(&file, "notes").unwrap();
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let mut entry = scanned(&file);
+        apply_rules_to_file(&config, &mut entry);
+        assert!(entry.matched.is_some());
+
+        let mut entry = scanned(&fifo);
+        assert!(matches!(entry.kind, FileKind::Other));
+        apply_rules_to_file(&config, &mut entry);
+        assert!(entry.matched.is_none());
+    }
 }
@@ -1,12 +1,17 @@
 use crate::run::entries::fs_entry::{EntryError, EntryOp, Outcome, Severity};
-use std::path::Path;
+use std::{io::Error, path::Path};
 
-pub fn parse_errors(path: &Path, entry_error: Option<EntryError>) -> EntryError {
-    entry_error.unwrap_or(EntryError {
+pub fn parse_errors(
+    path: &Path,
+    operation: EntryOp,
+    source: Option<Error>,
+    severity: Severity,
+) -> EntryError {
+    EntryError {
         path: path.to_path_buf(),
-        operation: EntryOp::ParsePath,
-        source: None,
-        severity: Severity::Warning,
+        operation,
+        source,
+        severity,
         outcome: Outcome::Skipped,
-    })
+    }
 }
@@ -1,16 +1,16 @@
 use crate::run::entries::fs_entry::{FileKind, FsEntry};
-use std::path::Path;
+use std::fs::FileType;
 
-pub fn set_entry_kind(entry: &mut FsEntry, path: &Path) {
-    if path.is_dir() {
+pub fn set_entry_kind(entry: &mut FsEntry, file_type: &FileType) {
+    // file_type comes from the directory entry, so symlinks are reported as-is
+    // instead of being followed to their target.
+    if file_type.is_symlink() {
+        entry.kind = FileKind::Symlink;
+    } else if file_type.is_dir() {
         entry.kind = FileKind::Directory;
+    } else if file_type.is_file() {
+        entry.kind = FileKind::File;
     } else {
-        if path.is_symlink() {
-            entry.kind = FileKind::Symlink;
-        } else if let Some(_) = path.extension() {
-            entry.kind = FileKind::File;
-        } else {
-            entry.kind = FileKind::Other;
-        }
+        entry.kind = FileKind::Other;
     }
 }
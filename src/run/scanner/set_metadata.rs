@@ -0,0 +1,106 @@
+use crate::run::{
+    entries::fs_entry::{EntryMetaData, EntryOp, FsEntry, Severity},
+    scanner::set_error::parse_errors,
+};
+use std::{
+    fs::{canonicalize, symlink_metadata},
+    io::Result,
+    path::Path,
+    time::SystemTime,
+};
+
+pub fn set_entry_metadata(entry: &mut FsEntry) {
+    // symlink_metadata so a symlink reports its own size/times, not its target's
+    let metadata = match symlink_metadata(&entry.path) {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            entry.errors.push(parse_errors(
+                &entry.path,
+                EntryOp::Metadata,
+                Some(error),
+                Severity::Error,
+            ));
+            return;
+        }
+    };
+
+    let mut entry_metadata = EntryMetaData {
+        size_bytes: Some(metadata.len()),
+        modified: read_time(entry, metadata.modified()),
+        created: read_time(entry, metadata.created()),
+        accessed: read_time(entry, metadata.accessed()),
+        readonly: Some(metadata.permissions().readonly()),
+        mime: guess_mime(&entry.path).map(String::from),
+        canonical_path: None,
+    };
+
+    match canonicalize(&entry.path) {
+        Ok(canonical_path) => entry_metadata.canonical_path = Some(canonical_path),
+        Err(error) => {
+            entry.errors.push(parse_errors(
+                &entry.path,
+                EntryOp::Canonicalize,
+                Some(error),
+                Severity::Warning,
+            ));
+        }
+    }
+
+    entry.metadata = Some(entry_metadata);
+}
+
+fn read_time(entry: &mut FsEntry, time: Result<SystemTime>) -> Option<SystemTime> {
+    match time {
+        Ok(time) => Some(time),
+        Err(error) => {
+            // not every platform/filesystem records every timestamp (e.g. birth time)
+            entry.errors.push(parse_errors(
+                &entry.path,
+                EntryOp::Metadata,
+                Some(error),
+                Severity::Warning,
+            ));
+            None
+        }
+    }
+}
+
+fn guess_mime(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let mime = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "heic" => "image/heic",
+        "tif" | "tiff" => "image/tiff",
+        "svg" => "image/svg+xml",
+        "mp4" => "video/mp4",
+        "mov" => "video/quicktime",
+        "mkv" => "video/x-matroska",
+        "avi" => "video/x-msvideo",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "pdf" => "application/pdf",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        "rar" => "application/vnd.rar",
+        "7z" => "application/x-7z-compressed",
+        "tar" => "application/x-tar",
+        "gz" => "application/gzip",
+        _ => return None,
+    };
+    Some(mime)
+}
@@ -0,0 +1,391 @@
+use crate::run::{
+    config::patterns::build_glob,
+    entries::fs_entry::{EntryError, EntryOp, Severity},
+    executor::transfer::{hash_file, move_file},
+    journal::{
+        Journal, PLAN_FILE, latest_records, list_runs, read_journal, read_run_info,
+        record::{JournalRecord, RecordStatus, RunMode},
+        runs_dir,
+    },
+    planner::{
+        plan::{Operation, PlanAction},
+        plan_file::load_plan,
+    },
+    quarantine::{QuarantineEntry, forget_entry},
+    scanner::{display::display_error, set_error::parse_errors},
+};
+use chrono::Local;
+use colored::*;
+use globset::GlobMatcher;
+use std::{
+    fs::{create_dir_all, remove_file, symlink_metadata},
+    io::Error,
+    path::{Path, PathBuf},
+};
+
+pub struct UndoFilter {
+    pub rule: Option<String>,
+    // matched against the original path relative to its root
+    pub glob: Option<String>,
+}
+
+// Reverses the committed operations of a run, newest first. A move is moved
+// back, a copy is removed again. Every check is repeated right before the file
+// is touched, and anything that no longer lines up is reported, never forced.
+// Each reversed operation is marked undone in the run's own journal, so a
+// later undo of the same run passes over it.
+// Returns whether the undo went through without conflicts or errors.
+pub fn undo_run(
+    state_dir: &Path,
+    run_id: Option<&str>,
+    filter: &UndoFilter,
+) -> Result<bool, String> {
+    let run_id = match run_id {
+        Some(run_id) => run_id.to_string(),
+        // undo runs are passed over, a second undo must not redo the first
+        None => list_runs(state_dir)?
+            .into_iter()
+            .rev()
+            .find(|run_id| {
+                read_run_info(&runs_dir(state_dir).join(run_id))
+                    .is_ok_and(|info| info.mode != RunMode::Undo)
+            })
+            .ok_or_else(|| format!("no runs to undo in {:?}", runs_dir(state_dir)))?,
+    };
+    let run_dir = runs_dir(state_dir).join(&run_id);
+    if !run_dir.is_dir() {
+        return Err(format!(
+            "run {} not found in {:?}",
+            run_id,
+            runs_dir(state_dir)
+        ));
+    }
+
+    let glob: Option<GlobMatcher> = match filter.glob.as_deref() {
+        Some(pattern) => Some(build_glob(pattern)?.compile_matcher()),
+        None => None,
+    };
+    // the plan knows the root and quarantine of every operation, the journal does not
+    let (roots, quarantines): (Vec<PathBuf>, Vec<Option<PathBuf>>) =
+        load_plan(&run_dir.join(PLAN_FILE))
+            .map(|plan| {
+                plan.operations
+                    .into_iter()
+                    .map(|op| (op.root, op.quarantine))
+                    .unzip()
+            })
+            .unwrap_or_default();
+
+    let mut records: Vec<JournalRecord> = latest_records(read_journal(&run_dir)?)
+        .into_iter()
+        .filter(|record| record.status == RecordStatus::Committed)
+        // a delete went to quarantine and moves back like any move, the
+        // deletes of an undo run removed copies and have no destination
+        .filter(|record| match record.action {
+            PlanAction::Move | PlanAction::Copy => true,
+            PlanAction::Delete => record.destination.is_some(),
+            PlanAction::Skip => false,
+        })
+        .filter(|record| {
+            filter
+                .rule
+                .as_ref()
+                .is_none_or(|rule| record.rule.as_ref() == Some(rule))
+        })
+        .filter(|record| {
+            glob.as_ref().is_none_or(|glob| {
+                let relative = roots
+                    .get(record.index)
+                    .and_then(|root| record.source.strip_prefix(root).ok())
+                    .unwrap_or(&record.source);
+                glob.is_match(relative)
+            })
+        })
+        .collect();
+    records.reverse();
+
+    println!(
+        "\n{} {}",
+        "UNDO".bright_yellow().bold(),
+        run_id.white().bold()
+    );
+    if records.is_empty() {
+        println!("Nothing to undo.");
+        return Ok(true);
+    }
+
+    let operations: Vec<Operation> = records
+        .iter()
+        .map(|record| undo_operation(record, &roots))
+        .collect();
+    let mut original = Journal::reopen(state_dir, &run_id)?;
+    let mut journal = Journal::start(state_dir, RunMode::Undo, &operations)?;
+    let mut restored = 0;
+    let mut conflicts = 0;
+    let mut errors: Vec<EntryError> = Vec::new();
+
+    for (index, (record, operation)) in records.iter().zip(&operations).enumerate() {
+        if let Err(conflict) = check_conflict(record, operation) {
+            conflicts += 1;
+            println!(
+                "  {} {} {}",
+                format!("{:<8}", "conflict").red().bold(),
+                operation.source.to_string_lossy().white(),
+                conflict.bright_black()
+            );
+            continue;
+        }
+
+        let pending = JournalRecord::new(
+            &journal.run_id,
+            index,
+            operation,
+            record.hash.clone(),
+            RecordStatus::Pending,
+        );
+        journal
+            .append(&pending)
+            .map_err(|e| journal_failure(&journal, e))?;
+
+        let result = undo_operation_on_disk(operation);
+        let mut outcome = pending.clone();
+        outcome.timestamp = Local::now().to_rfc3339();
+        outcome.status = RecordStatus::Committed;
+        if let Err(error) = result.as_ref() {
+            outcome.status = RecordStatus::Failed;
+            outcome.error = Some(error.to_string());
+        }
+        journal
+            .append(&outcome)
+            .map_err(|e| journal_failure(&journal, e))?;
+
+        match result {
+            Ok(()) => {
+                let mut undone = record.clone();
+                undone.timestamp = Local::now().to_rfc3339();
+                undone.status = RecordStatus::Undone;
+                original
+                    .append(&undone)
+                    .map_err(|e| journal_failure(&original, e))?;
+                // the file left the quarantine, so must its index entry
+                if let Some(quarantine) = quarantines.get(record.index).and_then(Option::as_deref)
+                    && let Err(error) =
+                        forget_entry(quarantine, &QuarantineEntry::id(&run_id, record.index))
+                {
+                    eprintln!("  {} {}", "warning".yellow().bold(), error);
+                }
+                restored += 1;
+                let label = match operation.action {
+                    PlanAction::Delete => "remove",
+                    _ => "restore",
+                };
+                println!(
+                    "  {} {} {} {}",
+                    format!("{:<8}", label).green().bold(),
+                    operation.source.to_string_lossy().white(),
+                    "→".bright_black(),
+                    operation
+                        .destination
+                        .as_deref()
+                        .map(|destination| destination.to_string_lossy().green())
+                        .unwrap_or_else(|| "removed".bright_black())
+                );
+            }
+            Err(error) => {
+                let entry_op = match operation.action {
+                    PlanAction::Delete => EntryOp::Delete,
+                    _ => EntryOp::Move,
+                };
+                let error = parse_errors(&operation.source, entry_op, Some(error), Severity::Error);
+                display_error(&error);
+                errors.push(error);
+            }
+        }
+    }
+
+    let dir = journal.dir.clone();
+    journal
+        .finish()
+        .map_err(|error| format!("cannot finish the run in {:?}: {}", dir, error))?;
+    println!(
+        "\n{} restored, {} conflicts, {} failed",
+        restored.to_string().green().bold(),
+        conflicts.to_string().yellow().bold(),
+        errors.len().to_string().red().bold()
+    );
+    Ok(conflicts == 0 && errors.is_empty())
+}
+
+// a move goes back where it came from, a copy is deleted again
+fn undo_operation(record: &JournalRecord, roots: &[PathBuf]) -> Operation {
+    let current = record.destination.clone().unwrap_or_default();
+    let root = roots.get(record.index).cloned().unwrap_or_default();
+    let (action, destination) = match record.action {
+        PlanAction::Copy => (PlanAction::Delete, None),
+        _ => (PlanAction::Move, Some(record.source.clone())),
+    };
+    Operation {
+        source: current,
+        root,
+        destination,
+        action,
+        rule: record.rule.clone(),
+        skip_reason: None,
+        source_size: record.size,
+        source_modified: None,
+        overwrite: false,
+        quarantine: None,
+    }
+}
+
+fn check_conflict(record: &JournalRecord, operation: &Operation) -> Result<(), String> {
+    let current = &operation.source;
+    if symlink_metadata(current).is_err() {
+        return Err("is no longer there".to_string());
+    }
+    if let Some(hash) = record.hash.as_ref() {
+        match hash_file(current) {
+            Ok(current_hash) if current_hash.to_hex().as_str() == hash => {}
+            Ok(_) => return Err("was modified after the run".to_string()),
+            Err(error) => return Err(format!("cannot be read: {}", error)),
+        }
+    }
+    match operation.destination.as_ref() {
+        Some(original) if symlink_metadata(original).is_ok() => Err(format!(
+            "original path {:?} is occupied",
+            original.to_string_lossy()
+        )),
+        // removing a copy is only safe while the file it was copied from exists
+        None if symlink_metadata(&record.source).is_err() => Err(format!(
+            "original {:?} is gone, this copy is the last one",
+            record.source.to_string_lossy()
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn undo_operation_on_disk(operation: &Operation) -> Result<(), Error> {
+    match operation.destination.as_deref() {
+        Some(original) => {
+            // the source directory may have been cleaned up since the run
+            if let Some(parent) = original.parent() {
+                create_dir_all(parent)?;
+            }
+            move_file(&operation.source, original, false)
+        }
+        None => remove_file(&operation.source),
+    }
+}
+
+fn journal_failure(journal: &Journal, error: Error) -> String {
+    format!("cannot write {:?}, undo stopped: {}", journal.path(), error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::run::executor::execute_plan;
+    use std::fs::{read_to_string, write};
+
+    const ALL: UndoFilter = UndoFilter {
+        rule: None,
+        glob: None,
+    };
+
+    // runs the operations for real, so the journal holds hashes like any run
+    fn executed(state_dir: &Path, operations: &[Operation]) -> String {
+        let mut journal = Journal::start(state_dir, RunMode::Run, operations).unwrap();
+        let errors = execute_plan(operations, &mut journal);
+        assert!(errors.is_empty());
+        let run_id = journal.run_id.clone();
+        journal.finish().unwrap();
+        run_id
+    }
+
+    fn moved(root: &Path, name: &str) -> Operation {
+        Operation::planned(
+            PlanAction::Move,
+            root.join(name),
+            root.to_path_buf(),
+            Some(root.join("docs").join(name)),
+        )
+    }
+
+    #[test]
+    fn second_undo_skips_what_the_first_undid() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, root) = (dir.path().join("state"), dir.path().join("root"));
+        create_dir_all(&root).unwrap();
+        write(root.join("a.txt"), "a").unwrap();
+        write(root.join("b.txt"), "b").unwrap();
+        let run_id = executed(&state, &[moved(&root, "a.txt"), moved(&root, "b.txt")]);
+
+        assert_eq!(undo_run(&state, Some(&run_id), &ALL), Ok(true));
+        assert_eq!(read_to_string(root.join("a.txt")).unwrap(), "a");
+        assert_eq!(read_to_string(root.join("b.txt")).unwrap(), "b");
+        let records = latest_records(read_journal(&runs_dir(&state).join(&run_id)).unwrap());
+        assert!(
+            records
+                .iter()
+                .all(|record| record.status == RecordStatus::Undone)
+        );
+
+        // nothing left to undo, in particular no conflict for the files put back
+        let runs = list_runs(&state).unwrap().len();
+        assert_eq!(undo_run(&state, Some(&run_id), &ALL), Ok(true));
+        assert_eq!(list_runs(&state).unwrap().len(), runs);
+    }
+
+    #[test]
+    fn conflicts_leave_every_file_where_it_is() {
+        let dir = tempfile::tempdir().unwrap();
+        let (state, root) = (dir.path().join("state"), dir.path().join("root"));
+        create_dir_all(&root).unwrap();
+        for name in ["modified.txt", "occupied.txt", "copied.txt"] {
+            write(root.join(name), name).unwrap();
+        }
+        let copy = Operation::planned(
+            PlanAction::Copy,
+            root.join("copied.txt"),
+            root.clone(),
+            Some(root.join("docs/copied.txt")),
+        );
+        let run_id = executed(
+            &state,
+            &[
+                moved(&root, "modified.txt"),
+                moved(&root, "occupied.txt"),
+                copy,
+            ],
+        );
+        write(root.join("docs/modified.txt"), "edited since").unwrap();
+        write(root.join("occupied.txt"), "a new file").unwrap();
+        remove_file(root.join("copied.txt")).unwrap();
+
+        assert_eq!(undo_run(&state, Some(&run_id), &ALL), Ok(false));
+        assert_eq!(
+            read_to_string(root.join("docs/modified.txt")).unwrap(),
+            "edited since"
+        );
+        assert!(!root.join("modified.txt").exists());
+        assert_eq!(
+            read_to_string(root.join("docs/occupied.txt")).unwrap(),
+            "occupied.txt"
+        );
+        assert_eq!(
+            read_to_string(root.join("occupied.txt")).unwrap(),
+            "a new file"
+        );
+        // the copy is the last one left, it stays
+        assert_eq!(
+            read_to_string(root.join("docs/copied.txt")).unwrap(),
+            "copied.txt"
+        );
+        let records = latest_records(read_journal(&runs_dir(&state).join(&run_id)).unwrap());
+        assert!(
+            records
+                .iter()
+                .all(|record| record.status == RecordStatus::Committed)
+        );
+    }
+}